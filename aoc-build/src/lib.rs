@@ -5,10 +5,11 @@
     clippy::missing_panics_doc
 )]
 
-use regex::Regex;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader};
-use syn::parse_quote;
+use eyre::WrapErr;
+use std::fs;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+use syn::{Meta, Token, parse_quote};
 
 fn output(content: &str) -> eyre::Result<()> {
     fs::write(
@@ -18,42 +19,95 @@ fn output(content: &str) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Solver declared by an `#[aoc(…)]` attribute.
+struct Solver {
+    day: usize,
+    part: usize,
+    version: Option<String>,
+    has_answer: bool,
+}
+
+/// Parse the arguments of an `#[aoc(…)]` attribute, such as
+/// `day3, part1, fast, answer = 42, param(width = 101)`, which may span
+/// several lines.
+fn parse_solver(attr: &syn::Attribute) -> eyre::Result<Solver> {
+    let args = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+    let mut args = args.iter();
+    let mut number = |prefix: &str| -> eyre::Result<usize> {
+        args.next()
+            .and_then(|arg| arg.path().get_ident())
+            .and_then(|ident| ident.to_string().strip_prefix(prefix)?.parse().ok())
+            .ok_or_else(|| eyre::eyre!("cannot find the {prefix} in an #[aoc] attribute"))
+    };
+    let (day, part) = (number("day")?, number("part")?);
+    let mut solver = Solver {
+        day,
+        part,
+        version: None,
+        has_answer: false,
+    };
+    for arg in args {
+        match arg {
+            Meta::Path(path) => {
+                solver.version = path.get_ident().map(ToString::to_string);
+            }
+            Meta::NameValue(nv) if nv.path.is_ident("answer") => solver.has_answer = true,
+            _ => (),
+        }
+    }
+    Ok(solver)
+}
+
+/// Write the registration of the solvers found in `src` to `register.rs`
+/// in the build output directory.
 pub fn build() -> eyre::Result<()> {
-    let attr_re = Regex::new(r"#\[aoc\(day(\d+),\s*part(\d+),?(.*)\)\]").unwrap();
-    let version_re = Regex::new(r"^\w+$").unwrap();
+    output(&generate(Path::new("src"))?)
+}
+
+/// Generate the `register_runners` function registering the solvers
+/// declared with `#[aoc(…)]` in the modules of the given directory.
+pub fn generate(dir: &Path) -> eyre::Result<String> {
     let mut refs = Vec::new();
-    for file in fs::read_dir("src")? {
+    for file in fs::read_dir(dir)? {
         let file = file?;
-        if !file
+        let Some(mod_name) = file
             .file_name()
             .into_string()
-            .is_ok_and(|s| s.ends_with(".rs"))
-        {
+            .ok()
+            .and_then(|s| s.strip_suffix(".rs").map(String::from))
+        else {
             continue;
-        }
-        for l in BufReader::new(File::open(file.path())?).lines() {
-            if let Some(m) = attr_re.captures(&l?) {
-                let day = m[1].parse::<usize>()?;
-                let part = m[2].parse::<usize>()?;
-                let version = m[3]
-                    .split(',')
-                    .map(str::trim)
-                    .find(|s| version_re.is_match(s));
-                let (version, extension): (syn::Expr, &str) = match version {
-                    Some(v) => (parse_quote!(Some(String::from(#v))), v),
-                    None => (parse_quote!(None), "none"),
-                };
-                let mod_name: syn::Ident = syn::parse_str(
-                    file.file_name()
-                        .into_string()
-                        .unwrap()
-                        .strip_suffix(".rs")
-                        .unwrap(),
-                )?;
-                let runner_name: syn::Ident =
-                    syn::parse_str(&format!("runner_{day}_{part}_{extension}"))?;
+        };
+        let mod_name: syn::Ident = syn::parse_str(&mod_name)?;
+        let path = file.path();
+        let content = syn::parse_file(&fs::read_to_string(&path)?)
+            .wrap_err_with(|| format!("cannot parse {}", path.display()))?;
+        let attrs = content.items.iter().flat_map(|item| match item {
+            syn::Item::Fn(func) => &func.attrs[..],
+            _ => &[],
+        });
+        for attr in attrs.filter(|attr| attr.path().is_ident("aoc")) {
+            let Solver {
+                day,
+                part,
+                version,
+                has_answer,
+            } = parse_solver(attr).wrap_err_with(|| format!("in {}", path.display()))?;
+            let (version, extension): (syn::Expr, &str) = match &version {
+                Some(v) => (parse_quote!(Some(String::from(#v))), v),
+                None => (parse_quote!(None), "none"),
+            };
+            let runner_name: syn::Ident =
+                syn::parse_str(&format!("runner_{day}_{part}_{extension}"))?;
+            let stmt: syn::Stmt = parse_quote! {
+                ::aoc::register_runner(#day, #part, #version, crate::#mod_name::#runner_name);
+            };
+            refs.push(stmt);
+            if has_answer {
+                let answer_name: syn::Ident =
+                    syn::parse_str(&format!("answer_{day}_{part}_{extension}"))?;
                 let stmt: syn::Stmt = parse_quote! {
-                    ::aoc::register_runner(#day, #part, #version, crate::#mod_name::#runner_name);
+                    ::aoc::register_answer(#day, #part, #version, crate::#mod_name::#answer_name());
                 };
                 refs.push(stmt);
             }
@@ -62,9 +116,9 @@ pub fn build() -> eyre::Result<()> {
     let register: syn::File = parse_quote! {
         #[allow(clippy::module_name_repetitions)]
         pub fn register_runners() {
+            ::aoc::register_crate(env!("CARGO_PKG_NAME"));
             #(#refs)*
         }
     };
-    output(&prettyplease::unparse(&register))?;
-    Ok(())
+    Ok(prettyplease::unparse(&register))
 }
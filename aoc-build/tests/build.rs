@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+#[test]
+fn multiline_attributes() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("multiline");
+    std::fs::create_dir_all(&dir).unwrap();
+    // rustfmt splits long attributes across lines
+    std::fs::write(
+        dir.join("day5.rs"),
+        r#"
+#[aoc(
+    day5,
+    part1,
+    wide_grid,
+    answer = 42,
+    param(width = 101, height = 103),
+    example_param(width = 11, height = 7)
+)]
+fn part1(input: &str, width: usize, height: usize) -> usize {
+    input.len() * width * height
+}
+
+// #[aoc(day5, part2)] is not a solver yet
+"#,
+    )
+    .unwrap();
+    insta::assert_snapshot!(aoc_build::generate(&dir).unwrap(), @r###"
+    #[allow(clippy::module_name_repetitions)]
+    pub fn register_runners() {
+        ::aoc::register_crate(env!("CARGO_PKG_NAME"));
+        ::aoc::register_runner(
+            5usize,
+            1usize,
+            Some(String::from("wide_grid")),
+            crate::day5::runner_5_1_wide_grid,
+        );
+        ::aoc::register_answer(
+            5usize,
+            1usize,
+            Some(String::from("wide_grid")),
+            crate::day5::answer_5_1_wide_grid(),
+        );
+    }
+    "###);
+}
@@ -4,17 +4,47 @@ use proc_macro::TokenStream;
 use proc_macro_error2::{abort, proc_macro_error};
 use quote::{ToTokens, quote};
 use syn::{
-    FnArg, Ident, ItemFn, LitChar, LitStr, PatType, ReturnType, Token,
+    Expr, FnArg, Ident, ItemFn, LitChar, LitInt, LitStr, Pat, PatType, ReturnType, Token,
     parse::{Parse, ParseStream},
     parse_macro_input,
+    punctuated::Punctuated,
 };
 
+/// Value given to a solver argument, such as `width = 101`.
+struct Param {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        <Token![=]>::parse(input)?;
+        Ok(Param {
+            name,
+            value: input.parse()?,
+        })
+    }
+}
+
+fn parse_params(input: ParseStream) -> syn::Result<Vec<Param>> {
+    let content;
+    syn::parenthesized!(content in input);
+    Ok(Punctuated::<Param, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
 #[derive(Default)]
 struct AocEntry {
     day: usize,
     part: usize,
     version: Option<String>,
     separator: Option<String>,
+    answer: Option<String>,
+    params: Vec<Param>,
+    example_params: Vec<Param>,
+    normalize: Option<Ident>,
 }
 
 impl Parse for AocEntry {
@@ -63,7 +93,8 @@ impl Parse for AocEntry {
         };
         while !input.is_empty() {
             <Token![,]>::parse(input)?;
-            match input.parse::<Ident>()?.to_string().as_str() {
+            let key = input.parse::<Ident>()?;
+            match key.to_string().as_str() {
                 "separator" => {
                     <Token![=]>::parse(input)?;
                     let lookahead = input.lookahead1();
@@ -76,6 +107,45 @@ impl Parse for AocEntry {
                         lookahead.error();
                     }
                 }
+                "answer" => {
+                    <Token![=]>::parse(input)?;
+                    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+                    let lookahead = input.lookahead1();
+                    if lookahead.peek(LitInt) {
+                        let answer = input.parse::<LitInt>().unwrap();
+                        let sign = if negative { "-" } else { "" };
+                        entry.answer = Some(format!("{sign}{}", answer.base10_digits()));
+                    } else if !negative && lookahead.peek(LitStr) {
+                        entry.answer = Some(input.parse::<LitStr>().unwrap().value());
+                    } else {
+                        return Err(lookahead.error());
+                    }
+                }
+                "normalize" => {
+                    <Token![=]>::parse(input)?;
+                    let policy = input.parse::<Ident>()?;
+                    let variant = match policy.to_string().as_str() {
+                        "raw" => "Raw",
+                        "lines" => "Lines",
+                        "trim" => "Trim",
+                        _ => {
+                            return Err(syn::Error::new(
+                                policy.span(),
+                                "normalization must be `raw`, `lines` or `trim`",
+                            ));
+                        }
+                    };
+                    entry.normalize = Some(Ident::new(variant, policy.span()));
+                }
+                "param" => entry.params = parse_params(input)?,
+                "example_param" => entry.example_params = parse_params(input)?,
+                // `main` designates the main version in filters and answers files
+                "main" => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "`main` is reserved for the version without a name",
+                    ));
+                }
                 i => entry.version = Some(i.to_owned()),
             }
         }
@@ -83,6 +153,21 @@ impl Parse for AocEntry {
     }
 }
 
+/// Name of a solver argument given as a plain identifier.
+fn param_name(arg: &FnArg) -> Option<Ident> {
+    match arg {
+        FnArg::Typed(PatType { pat, .. }) => match &**pat {
+            Pat::Ident(ident) => Some(ident.ident.clone()),
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    }
+}
+
+fn is_param(params: &[Param], name: &Ident) -> bool {
+    params.iter().any(|p| p.name == *name)
+}
+
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn aoc(attr: TokenStream, input: TokenStream) -> TokenStream {
@@ -92,61 +177,133 @@ pub fn aoc(attr: TokenStream, input: TokenStream) -> TokenStream {
     let version = aoc_entry.version;
     let func = parse_macro_input!(input as ItemFn);
     let func_name = func.sig.ident.clone();
-    let runner_func_name = Ident::new(
-        &format!(
-            "runner_{}_{}_{}",
-            day,
-            part,
-            version.clone().unwrap_or_else(|| String::from("none"))
-        ),
-        func.sig.ident.span(),
+    let suffix = format!(
+        "{day}_{part}_{}",
+        version.clone().unwrap_or_else(|| String::from("none"))
+    );
+    let runner_func_name = Ident::new(&format!("runner_{suffix}"), func.sig.ident.span());
+    let answer = aoc_entry.answer.map(|answer| {
+        let answer_func_name = Ident::new(&format!("answer_{suffix}"), func.sig.ident.span());
+        let test_name = Ident::new(&format!("{func_name}_answer"), func.sig.ident.span());
+        quote! {
+            pub fn #answer_func_name() -> &'static str {
+                #answer
+            }
+
+            #[cfg(test)]
+            #[test]
+            fn #test_name() {
+                ::aoc::test::assert_answer(#answer, #runner_func_name);
+            }
+        }
+    });
+    let normalization = aoc_entry.normalize.as_ref().map_or_else(
+        || quote!(::aoc::input::Normalization::default()),
+        |variant| quote!(::aoc::input::Normalization::#variant),
     );
     let sep = if let Some(sep) = aoc_entry.separator {
         quote!(Some(#sep))
     } else {
         quote!(None)
     };
-    let inputs = match func.sig.inputs.first() {
+    // The first argument receives the input, unless it is a parameter
+    let input_arg = func
+        .sig
+        .inputs
+        .first()
+        .filter(|arg| param_name(arg).is_none_or(|name| !is_param(&aoc_entry.params, &name)));
+    let input = match input_arg {
         Some(FnArg::Typed(PatType { ty, .. }))
             if quote!(#ty).to_string().contains("Vec < & [u8] >") =>
         {
-            quote!((::aoc::input::parse_input_bytes(&::aoc::input::input_bytes(#day)?, #sep.map(|c: char| c as u8))?))
+            Some(
+                quote!(::aoc::input::parse_input_bytes(&::aoc::input::input_bytes_with(#day, #normalization)?, #sep.map(|c: char| c as u8))?),
+            )
         }
         Some(FnArg::Typed(PatType { ty, .. }))
             if quote!(#ty).to_string().contains("& [& [u8]]") =>
         {
-            quote!((&::aoc::input::parse_input_bytes(&::aoc::input::input_bytes(#day)?, #sep.map(|c: char| c as u8))?))
+            Some(
+                quote!(&::aoc::input::parse_input_bytes(&::aoc::input::input_bytes_with(#day, #normalization)?, #sep.map(|c: char| c as u8))?),
+            )
         }
         Some(FnArg::Typed(PatType { ty, .. }))
             if quote!(#ty).to_string().contains("Vec < & str >") =>
         {
-            quote!((::aoc::input::input_string(#day)?.lines().collect()))
+            Some(quote!(::aoc::input::input_string_with(#day, #normalization)?.lines().collect()))
         }
         Some(FnArg::Typed(PatType { ty, .. })) if quote!(#ty).to_string().contains("& [& str]") => {
-            quote!((&::aoc::input::input_string(#day)?.lines().collect::<Vec<_>>()))
+            Some(
+                quote!(&::aoc::input::input_string_with(#day, #normalization)?.lines().collect::<Vec<_>>()),
+            )
         }
         Some(FnArg::Typed(PatType { ty, .. }))
             if quote!(#ty).to_string().contains("& mut [& str]") =>
         {
-            quote!((&mut ::aoc::input::input_string(#day)?.lines().collect::<Vec<_>>()))
+            Some(
+                quote!(&mut ::aoc::input::input_string_with(#day, #normalization)?.lines().collect::<Vec<_>>()),
+            )
         }
         Some(FnArg::Typed(PatType { ty, .. })) if quote!(#ty).to_string().contains("& str") => {
-            quote!((&::aoc::input::input_string(#day)?))
+            Some(quote!(&::aoc::input::input_string_with(#day, #normalization)?))
         }
         Some(FnArg::Typed(PatType { ty, .. })) if quote!(#ty).to_string().contains("& [u8]") => {
-            quote!((&::aoc::input::input_bytes(#day)?))
+            Some(quote!(&::aoc::input::input_bytes_with(#day, #normalization)?))
         }
         Some(FnArg::Typed(PatType { ty, .. })) if quote!(#ty).to_string().contains("Vec <") => {
-            quote!((::aoc::input::parse_input(&::aoc::input::input_string(#day)?, #sep)?))
+            Some(
+                quote!(::aoc::input::parse_day_input(#day, &::aoc::input::input_string_with(#day, #normalization)?, #sep)?),
+            )
         }
         Some(FnArg::Typed(PatType { ty, .. })) if quote!(#ty).to_string().contains("& mut [") => {
-            quote!((&mut ::aoc::input::parse_input(&::aoc::input::input_string(#day)?, #sep)?))
-        }
-        Some(_) => {
-            quote!((&::aoc::input::parse_input(&::aoc::input::input_string(#day)?, #sep)?))
+            Some(
+                quote!(&mut ::aoc::input::parse_day_input(#day, &::aoc::input::input_string_with(#day, #normalization)?, #sep)?),
+            )
         }
-        None => quote!(()),
+        Some(_) => Some(
+            quote!(&::aoc::input::parse_day_input(#day, &::aoc::input::input_string_with(#day, #normalization)?, #sep)?),
+        ),
+        None => None,
     };
+    let params = func
+        .sig
+        .inputs
+        .iter()
+        .skip(usize::from(input.is_some()))
+        .map(|arg| {
+            let Some(name) = param_name(arg).filter(|name| is_param(&aoc_entry.params, name))
+            else {
+                abort!(arg, "solver argument has no value in `param(…)`");
+            };
+            let find = |params: &[Param]| {
+                params
+                    .iter()
+                    .find(|p| p.name == name)
+                    .map(|p| p.value.clone())
+            };
+            let value = find(&aoc_entry.params).unwrap();
+            if let Some(example) = find(&aoc_entry.example_params) {
+                quote!(if ::aoc::input::is_example() { #example } else { #value })
+            } else {
+                quote!(#value)
+            }
+        })
+        .collect::<Vec<_>>();
+    for param in aoc_entry.params.iter().chain(&aoc_entry.example_params) {
+        if !func
+            .sig
+            .inputs
+            .iter()
+            .any(|arg| param_name(arg).as_ref() == Some(&param.name))
+        {
+            abort!(param.name, "no solver argument named `{}`", param.name);
+        }
+        if !is_param(&aoc_entry.params, &param.name) {
+            abort!(param.name, "`{}` is missing from `param(…)`", param.name);
+        }
+    }
+    let inputs = input.into_iter().chain(params);
+    let inputs = quote!((#(#inputs),*));
     let (call, ty) = match func.sig.output {
         ReturnType::Type(_, ref t) if t.to_token_stream().to_string().contains("Result < ") => {
             (quote!(#func_name #inputs), quote!(#t))
@@ -172,6 +329,8 @@ pub fn aoc(attr: TokenStream, input: TokenStream) -> TokenStream {
         pub fn #runner_func_name() -> #ty {
             #call
         }
+
+        #answer
     }
     .into()
 }
@@ -0,0 +1,157 @@
+use crate::expected::Key;
+use eyre::{OptionExt, bail};
+use std::collections::BTreeMap;
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Name of the key holding the answer of the main version.
+const MAIN: &str = "main";
+
+fn number(name: &str, prefix: &str) -> eyre::Result<usize> {
+    name.strip_prefix(prefix)
+        .and_then(|n| n.parse().ok())
+        .ok_or_eyre(format!("{name} is not a valid key (`{prefix}N` expected)"))
+}
+
+/// Name of the table holding the answers of the current year, such as
+/// `year2023`, if the answers are grouped by year. If the current year is
+/// unknown, a single year table is used.
+fn year_table(document: &Table) -> eyre::Result<Option<String>> {
+    let years = document
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with("year"))
+        .collect::<Vec<_>>();
+    for name in &years {
+        number(name, "year")?;
+    }
+    match (super::input::current_year(), &years[..]) {
+        (_, []) => Ok(None),
+        (Some(year), _) => Ok(Some(format!("year{year}"))),
+        (None, [name]) => Ok(Some((*name).to_owned())),
+        (None, _) => bail!(
+            "cannot determine the year of the answers, set {}",
+            super::input::YEAR_ENV_VAR
+        ),
+    }
+}
+
+/// Parse the answers of the days of a table.
+fn parse_days(
+    table: &dyn TableLike,
+    prefix: &str,
+    entries: &mut BTreeMap<Key, String>,
+) -> eyre::Result<()> {
+    for (day_name, day_item) in table.iter() {
+        let day = number(day_name, "day")?;
+        let Some(day_table) = day_item.as_table_like() else {
+            bail!("{prefix}{day_name} must be a table");
+        };
+        for (part_name, part_item) in day_table.iter() {
+            let part = number(part_name, "part")?;
+            let Some(part_table) = part_item.as_table_like() else {
+                bail!("{prefix}{day_name}.{part_name} must be a table");
+            };
+            for (version, answer) in part_table.iter() {
+                let answer = match answer.as_value() {
+                    Some(Value::String(s)) => s.value().trim().to_owned(),
+                    Some(Value::Integer(i)) => i.value().to_string(),
+                    _ => bail!(
+                        "{prefix}{day_name}.{part_name}.{version} must be a string or an integer"
+                    ),
+                };
+                let version = (version != MAIN).then(|| version.to_owned());
+                entries.insert((day, part, version), answer);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse answers stored in TOML as
+///
+/// ```toml
+/// [day1.part1]
+/// main = 232
+/// str_slice = 232
+/// ```
+///
+/// The days may also be grouped by year, such as `[year2015.day1.part1]`, in
+/// which case only the answers of the current year are used.
+pub(crate) fn parse(content: &str) -> eyre::Result<BTreeMap<Key, String>> {
+    let document = content.parse::<DocumentMut>()?;
+    let year = year_table(document.as_table())?;
+    let mut days = Table::new();
+    for (name, item) in document.iter() {
+        if !name.starts_with("year") {
+            days.insert(name, item.clone());
+        }
+    }
+    let mut entries = BTreeMap::new();
+    parse_days(&days, "", &mut entries)?;
+    // Answers of the year take precedence over the ones given without year
+    if let Some(year) = year
+        && let Some(item) = document.get(&year)
+    {
+        let Some(year_table) = item.as_table_like() else {
+            bail!("{year} must be a table");
+        };
+        parse_days(year_table, &format!("{year}."), &mut entries)?;
+    }
+    Ok(entries)
+}
+
+fn to_value(answer: &str) -> Value {
+    if let Ok(n) = answer.parse::<i64>()
+        && n.to_string() == answer
+    {
+        Value::from(n)
+    } else if answer.contains('\n') && !answer.contains("'''") {
+        // Multi-line literal strings keep answers such as drawings readable
+        format!("'''\n{answer}'''")
+            .parse()
+            .unwrap_or_else(|_| Value::from(answer))
+    } else {
+        Value::from(answer)
+    }
+}
+
+fn subtable<'a>(table: &'a mut Table, key: &str) -> eyre::Result<&'a mut Table> {
+    let item = table.entry(key).or_insert_with(|| {
+        let mut table = Table::new();
+        table.set_implicit(true);
+        Item::Table(table)
+    });
+    item.as_table_mut()
+        .ok_or_eyre(format!("{key} must be a table"))
+}
+
+/// Update the answers stored in `content`, leaving unchanged entries,
+/// comments and formatting untouched. If the answers are grouped by year,
+/// they are stored in the table of the current year.
+pub(crate) fn record(content: &str, answers: &[(Key, String)]) -> eyre::Result<String> {
+    let mut document = content.parse::<DocumentMut>()?;
+    let year = year_table(document.as_table())?;
+    for ((day, part, version), answer) in answers {
+        let days = match &year {
+            Some(year) => subtable(document.as_table_mut(), year)?,
+            None => document.as_table_mut(),
+        };
+        let day = subtable(days, &format!("day{day}"))?;
+        let part = subtable(day, &format!("part{part}"))?;
+        part.set_implicit(false);
+        let version = version.as_deref().unwrap_or(MAIN);
+        let previous = part.get(version).and_then(Item::as_value);
+        let unchanged = previous.is_some_and(|v| {
+            v.as_str().map(str::trim) == Some(answer)
+                || v.as_integer().is_some_and(|i| &i.to_string() == answer)
+        });
+        if !unchanged {
+            let mut answer = to_value(answer);
+            if let Some(previous) = previous {
+                *answer.decor_mut() = previous.decor().clone();
+            }
+            part.insert(version, Item::Value(answer));
+        }
+    }
+    Ok(document.to_string())
+}
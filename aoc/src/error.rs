@@ -14,6 +14,35 @@ pub enum Error {
         part: usize,
         variant: Option<&'static str>,
     },
+    #[error("no solver registered for day {day} part {part}{}", variant.as_ref().map(|v| format!(r#" (variant "{v}")"#)).unwrap_or_default())]
+    NoSolver {
+        day: usize,
+        part: usize,
+        variant: Option<String>,
+    },
+    #[error("{}", render_parse_error(*day, *line, *column, content))]
+    Parse {
+        /// Day of the input, if known
+        day: Option<usize>,
+        /// Line of the offending item, starting at 1
+        line: usize,
+        /// Column of the offending item in characters, starting at 1
+        column: usize,
+        /// Content of the line
+        content: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// Describe the location of an item which cannot be parsed, with a caret
+/// under it.
+fn render_parse_error(day: Option<usize>, line: usize, column: usize, content: &str) -> String {
+    let margin = " ".repeat(line.to_string().len());
+    let day = day.map(|day| format!(" of day {day}")).unwrap_or_default();
+    format!(
+        "cannot parse line {line}, column {column} of the input{day}\n{margin} |\n{line} | {content}\n{margin} | {}^",
+        " ".repeat(column.saturating_sub(1))
+    )
 }
 
 impl Error {
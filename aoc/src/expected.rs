@@ -0,0 +1,105 @@
+use eyre::{OptionExt, bail};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Identification of a solver: day, part and version.
+pub(crate) type Key = (usize, usize, Option<String>);
+
+fn parse_header(header: &str) -> eyre::Result<Key> {
+    let err = || format!("malformed expected result header {header:?}");
+    let rest = header.strip_prefix("Day ").ok_or_eyre(err())?;
+    let (day, rest) = rest.split_once(" - part ").ok_or_eyre(err())?;
+    let rest = rest.strip_suffix(" [differs from main]").unwrap_or(rest);
+    let (part, version) = match rest.split_once(" — ") {
+        Some((part, version)) => (part, Some(version.to_owned())),
+        None => (rest, None),
+    };
+    Ok((day.parse()?, part.parse()?, version))
+}
+
+/// Parse expected results in the format produced by a text run, where
+/// continuation lines of multi-line answers are indented to the answer
+/// column of the first line.
+pub(crate) fn parse(content: &str) -> eyre::Result<BTreeMap<Key, String>> {
+    let mut entries = BTreeMap::new();
+    let mut current: Option<(Key, String, usize)> = None;
+    for line in content.lines() {
+        if line.starts_with("Day ") {
+            if let Some((key, answer, _)) = current.take() {
+                entries.insert(key, answer);
+            }
+            let Some((header, answer)) = line.split_once(':') else {
+                bail!("missing answer in expected result {line:?}");
+            };
+            let indent = header.chars().count() + 2;
+            let answer = answer.strip_prefix(' ').unwrap_or(answer);
+            current = Some((parse_header(header)?, answer.to_owned(), indent));
+        } else if let Some((_, answer, indent)) = &mut current {
+            let spaces = line.chars().take(*indent).take_while(|&c| c == ' ').count();
+            answer.push('\n');
+            answer.push_str(&line[spaces..]);
+        } else if !line.trim().is_empty() {
+            bail!("unexpected line {line:?} in expected results");
+        }
+    }
+    if let Some((key, answer, _)) = current {
+        entries.insert(key, answer);
+    }
+    Ok(entries
+        .into_iter()
+        .map(|(key, answer)| (key, answer.trim().to_owned()))
+        .collect())
+}
+
+/// Check whether expected results are stored as structured TOML answers
+/// rather than as the text output of a run.
+pub(crate) fn is_structured(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+/// Expected results file of an example or additional input, next to the
+/// main one, such as `expected-alice.txt` for `expected.txt`.
+pub(crate) fn for_input(path: &Path, label: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(match path.extension() {
+        Some(extension) => format!("{stem}-{label}.{}", extension.to_string_lossy()),
+        None => format!("{stem}-{label}"),
+    })
+}
+
+/// Expected results file of a user, such as `expected-user-alice.txt` for
+/// `expected.txt`, distinct from the one of an additional input of the same
+/// name.
+pub(crate) fn for_user(path: &Path, user: &str) -> PathBuf {
+    for_input(path, &format!("user-{user}"))
+}
+
+/// Read expected results from a file, which might not exist yet.
+pub(crate) fn read(path: &Path) -> eyre::Result<BTreeMap<Key, String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) if is_structured(path) => super::answers::parse(&content),
+        Ok(content) => parse(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Look up the expected answer of a solver. If `variants_follow_main` is
+/// set, a version without an expected answer of its own is expected to
+/// give the same answer as the main version.
+pub(crate) fn lookup<'a>(
+    expected: &'a BTreeMap<Key, String>,
+    day: usize,
+    part: usize,
+    version: Option<&str>,
+    variants_follow_main: bool,
+) -> Option<&'a str> {
+    expected
+        .get(&(day, part, version.map(String::from)))
+        .or_else(|| {
+            variants_follow_main
+                .then(|| expected.get(&(day, part, None)))
+                .flatten()
+        })
+        .map(String::as_str)
+}
@@ -0,0 +1,45 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::Write;
+
+/// Prefix of hashed answers, which are stored as `hmac-sha256:HEX`. The key
+/// is not stored: without it, answers cannot be found back by hashing
+/// candidates.
+const PREFIX: &str = "hmac-sha256:";
+
+/// Environment variable holding the key used to hash answers.
+pub(crate) const KEY_ENV_VAR: &str = "AOC_ANSWER_SALT";
+
+/// Key given explicitly, or else found in the environment.
+pub(crate) fn key(given: Option<&str>) -> Option<String> {
+    given
+        .map(String::from)
+        .or_else(|| std::env::var(KEY_ENV_VAR).ok())
+}
+
+/// Hash an answer with the given key, in the form stored in expected
+/// results files.
+pub(crate) fn hash(key: &str, answer: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+    mac.update(answer.as_bytes());
+    let mut hashed = String::from(PREFIX);
+    for byte in mac.finalize().into_bytes() {
+        write!(&mut hashed, "{byte:02x}").unwrap();
+    }
+    hashed
+}
+
+/// Check whether an expected answer is stored as a hash.
+pub(crate) fn is_hashed(expected: &str) -> bool {
+    expected.starts_with(PREFIX)
+}
+
+/// Check whether an answer matches the expected one, which might be
+/// hashed. A hashed answer never matches without a key.
+pub(crate) fn matches(key: Option<&str>, expected: &str, answer: &str) -> bool {
+    match key {
+        _ if !is_hashed(expected) => expected == answer,
+        Some(key) => hash(key, answer) == expected,
+        None => false,
+    }
+}
@@ -1,50 +1,508 @@
 #![allow(clippy::module_name_repetitions)]
 
 use crate::error::Error;
-use std::{str::FromStr, sync::OnceLock};
+use crate::run::Opts;
+use eyre::Context;
+use std::io::Read;
+use std::{cell::RefCell, collections::BTreeSet, fmt, path::PathBuf, str::FromStr};
 
-pub static OVERRIDE_INPUT: OnceLock<String> = OnceLock::new();
+/// Input given on the command line instead of the input file of the day.
+#[derive(Clone, Debug)]
+pub enum InputSource {
+    /// Content of a file
+    File { path: PathBuf, content: Vec<u8> },
+    /// Text given on the command line, to which a newline is added
+    String(String),
+    /// Content of the standard input
+    Stdin(Vec<u8>),
+    /// Text used without any newline being added
+    Text(String),
+}
 
-pub fn input_bytes(day: usize) -> Result<Vec<u8>, Error> {
-    match OVERRIDE_INPUT.get() {
-        Some(s) => Ok(std::fs::read(s).unwrap_or_else(|_| {
-            let mut s = s.as_bytes().to_vec();
-            s.push(b'\n');
-            s
-        })),
-        None => Ok(std::fs::read(format!("input/day{day}.txt"))?),
+impl InputSource {
+    /// Read the content of a file.
+    pub fn from_file(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let content = std::fs::read(&path)?;
+        Ok(InputSource::File { path, content })
+    }
+
+    /// Content of the input.
+    #[must_use]
+    pub fn content(&self) -> Vec<u8> {
+        match self {
+            InputSource::File { content, .. } | InputSource::Stdin(content) => content.clone(),
+            InputSource::String(s) => format!("{s}\n").into_bytes(),
+            InputSource::Text(s) => s.clone().into_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File { path, .. } => write!(f, "file {}", path.display()),
+            InputSource::String(_) => write!(f, "string given on the command line"),
+            InputSource::Stdin(_) => write!(f, "standard input"),
+            InputSource::Text(_) => write!(f, "given text"),
+        }
+    }
+}
+
+/// Read the alternate input designated on the command line, if any.
+fn alternate_input(opts: &Opts) -> eyre::Result<Option<InputSource>> {
+    Ok(match (&opts.input, &opts.input_string) {
+        (Some(path), _) if path == "-" => {
+            let mut content = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut content)
+                .context("cannot read standard input")?;
+            Some(InputSource::Stdin(content))
+        }
+        (Some(path), _) => Some(
+            InputSource::from_file(path)
+                .with_context(|| format!("cannot read input file {path}"))?,
+        ),
+        (None, Some(s)) => Some(InputSource::String(s.clone())),
+        (None, None) => None,
+    })
+}
+
+const DIR_ENV_VAR: &str = "AOC_INPUT_DIR";
+const TEMPLATE_ENV_VAR: &str = "AOC_INPUT_TEMPLATE";
+pub(crate) const YEAR_ENV_VAR: &str = "AOC_YEAR";
+pub(crate) const USER_ENV_VAR: &str = "AOC_USER";
+
+/// Year of the puzzles, given by `AOC_YEAR` or found in the name of the
+/// crate such as `aoc2023`. This name is the one registered along with the
+/// solvers, or else the one given by cargo when running them.
+pub(crate) fn current_year() -> Option<String> {
+    std::env::var(YEAR_ENV_VAR).ok().or_else(|| {
+        let name = super::runners::crate_name()
+            .map(String::from)
+            .or_else(|| std::env::var("CARGO_PKG_NAME").ok())?;
+        let start = name.find(|c: char| c.is_ascii_digit())?;
+        let year = name[start..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        (year.len() == 4).then_some(year)
+    })
+}
+
+/// Location of the input files, such as `input/day{day}.txt`.
+#[derive(Clone, Debug)]
+pub(crate) struct InputLayout {
+    dir: PathBuf,
+    template: String,
+    year: Option<String>,
+    /// Profile whose inputs are in a subdirectory of its own
+    user: Option<String>,
+}
+
+impl InputLayout {
+    /// Build the layout from the given directory and file name template,
+    /// defaulting to the `AOC_INPUT_DIR` and `AOC_INPUT_TEMPLATE`
+    /// environment variables, then to `input` and `day{day}.txt`. A
+    /// directory not given explicitly is relative to the crate being run
+    /// when launched from `cargo`. The inputs of a user, given explicitly or
+    /// through `AOC_USER`, are in a subdirectory named after them.
+    fn new(
+        dir: Option<PathBuf>,
+        template: Option<String>,
+        user: Option<String>,
+    ) -> eyre::Result<Self> {
+        let dir = dir.unwrap_or_else(|| {
+            let dir =
+                std::env::var_os(DIR_ENV_VAR).map_or_else(|| PathBuf::from("input"), PathBuf::from);
+            match std::env::var_os("CARGO_MANIFEST_DIR") {
+                Some(manifest_dir) => PathBuf::from(manifest_dir).join(dir),
+                None => dir,
+            }
+        });
+        let user = user.or_else(|| std::env::var(USER_ENV_VAR).ok());
+        let dir = match &user {
+            Some(user) => dir.join(user),
+            None => dir,
+        };
+        let template = template
+            .or_else(|| std::env::var(TEMPLATE_ENV_VAR).ok())
+            .unwrap_or_else(|| String::from("day{day}.txt"));
+        let layout = InputLayout {
+            dir,
+            template,
+            year: current_year(),
+            user,
+        };
+        if layout.year.is_none() && layout.template.contains("{year}") {
+            eyre::bail!("cannot determine the year of the input files, set {YEAR_ENV_VAR}");
+        }
+        let name = layout.file_name(1);
+        if name.contains(['{', '}']) {
+            eyre::bail!(
+                "invalid input file template {} (`{{day}}`, `{{day:02}}` and `{{year}}` are recognized)",
+                layout.template
+            );
+        }
+        Ok(layout)
+    }
+
+    /// Path of an example input of a day: `dayN.example.txt` for the first
+    /// example if it exists, `dayN/exampleK.txt` otherwise.
+    fn example_file(&self, day: usize, example: usize) -> PathBuf {
+        let file = self.dir.join(format!("day{day}.example.txt"));
+        if example == 1 && file.exists() {
+            file
+        } else {
+            self.day_dir(day).join(format!("example{example}.txt"))
+        }
+    }
+
+    /// Directory holding the examples and additional inputs of a day.
+    fn day_dir(&self, day: usize) -> PathBuf {
+        self.dir.join(format!("day{day}"))
+    }
+
+    /// Names of the additional inputs of a day, such as `alice` for
+    /// `day5/alice.txt`. Examples are not included.
+    fn input_names(&self, day: usize) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(self.day_dir(day)) else {
+            return Vec::new();
+        };
+        let mut names = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_stem()?.to_str()?;
+                (path.extension()? == "txt" && !name.starts_with("example"))
+                    .then(|| name.to_owned())
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn file_name(&self, day: usize) -> String {
+        self.template
+            .replace("{day}", &day.to_string())
+            .replace("{day:02}", &format!("{day:02}"))
+            .replace("{year}", self.year.as_deref().unwrap_or("{year}"))
     }
 }
 
+/// Input used by the solvers: an alternate input, or the input files of
+/// the days at some location.
+#[derive(Clone, Debug, Default)]
+pub struct InputContext {
+    source: Option<InputSource>,
+    /// Example to use instead of the input file of the day
+    example: Option<usize>,
+    /// Additional input to use instead of the input file of the day
+    name: Option<String>,
+    /// Location of the input files, from the environment if absent
+    layout: Option<InputLayout>,
+}
+
+impl InputContext {
+    /// Build the context from the command line options.
+    pub(crate) fn from_opts(opts: &Opts) -> eyre::Result<Self> {
+        Ok(InputContext {
+            source: alternate_input(opts)?,
+            example: opts.example,
+            name: opts.input_name.clone(),
+            layout: Some(InputLayout::new(
+                opts.input_dir.clone(),
+                opts.input_template.clone(),
+                opts.user.clone(),
+            )?),
+        })
+    }
+
+    /// Use the input files of the given directory rather than the one given
+    /// by `AOC_INPUT_DIR`, as with `--input-dir`.
+    pub fn from_dir(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+        Ok(InputContext {
+            layout: Some(InputLayout::new(Some(dir.into()), None, None)?),
+            ..Default::default()
+        })
+    }
+
+    /// Alternate input replacing the input files, if any.
+    #[must_use]
+    pub fn source(&self) -> Option<&InputSource> {
+        self.source.as_ref()
+    }
+
+    /// Example used instead of the input files, if any.
+    #[must_use]
+    pub fn example(&self) -> Option<usize> {
+        self.example
+    }
+
+    /// Mark the input as an example, so that solvers use their example
+    /// parameters.
+    #[must_use]
+    pub fn as_example(mut self, example: usize) -> Self {
+        self.example = Some(example);
+        self
+    }
+
+    /// Profile whose inputs and expected answers are used, if any.
+    pub(crate) fn user(&self) -> Result<Option<String>, Error> {
+        self.with_layout(|layout| layout.user.clone())
+    }
+
+    /// Name of the additional input used instead of the input files, if any.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Use the additional input of the given name.
+    pub(crate) fn named(&self, name: &str) -> Self {
+        InputContext {
+            name: Some(name.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// Label distinguishing the expected results of an example or
+    /// additional input from the ones of the input files.
+    pub(crate) fn label(&self) -> Option<String> {
+        match (&self.name, self.example) {
+            (Some(name), _) => Some(name.clone()),
+            (None, Some(example)) => Some(format!("example{example}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Check whether the real input files of the days are used, for the
+    /// default user.
+    pub(crate) fn is_puzzle_input(&self) -> bool {
+        self.source.is_none()
+            && self.example.is_none()
+            && self.name.is_none()
+            && self.user().is_ok_and(|user| user.is_none())
+    }
+
+    /// Check whether a day is skipped because it lacks the requested
+    /// example or additional input.
+    pub(crate) fn skips(&self, day: usize) -> bool {
+        (self.example.is_some() || self.name.is_some()) && self.is_missing(day)
+    }
+
+    fn with_layout<T>(&self, f: impl FnOnce(&InputLayout) -> T) -> Result<T, Error> {
+        match &self.layout {
+            Some(layout) => Ok(f(layout)),
+            None => Ok(f(&InputLayout::new(None, None, None)?)),
+        }
+    }
+
+    /// Names of the additional inputs of the given days.
+    pub(crate) fn input_names(
+        &self,
+        days: impl IntoIterator<Item = usize>,
+    ) -> Result<BTreeSet<String>, Error> {
+        self.with_layout(|layout| {
+            days.into_iter()
+                .flat_map(|day| layout.input_names(day))
+                .collect()
+        })
+    }
+
+    /// Path of the input file of a day.
+    pub(crate) fn input_file(&self, day: usize) -> Result<PathBuf, Error> {
+        self.with_layout(|layout| match (&self.name, self.example) {
+            (Some(name), _) => layout.day_dir(day).join(format!("{name}.txt")),
+            (None, Some(example)) => layout.example_file(day, example),
+            (None, None) => layout.dir.join(layout.file_name(day)),
+        })
+    }
+
+    /// Check whether the input or example file of a day is missing while no
+    /// alternate input has been given.
+    pub(crate) fn is_missing(&self, day: usize) -> bool {
+        self.source.is_none() && self.input_file(day).is_ok_and(|path| !path.exists())
+    }
+
+    /// Input of a day.
+    pub fn input_bytes(&self, day: usize) -> Result<Vec<u8>, Error> {
+        match &self.source {
+            Some(input) => Ok(input.content()),
+            None => Ok(std::fs::read(self.input_file(day)?)?),
+        }
+    }
+}
+
+impl From<InputSource> for InputContext {
+    fn from(source: InputSource) -> Self {
+        InputContext {
+            source: Some(source),
+            example: None,
+            name: None,
+            layout: None,
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_INPUT: RefCell<Option<InputContext>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous input context of the thread when dropped.
+struct RestoreInput(Option<InputContext>);
+
+impl Drop for RestoreInput {
+    fn drop(&mut self) {
+        CURRENT_INPUT.set(self.0.take());
+    }
+}
+
+/// Run `f` with solvers reading their input from `context` on the current
+/// thread. Other threads are not affected, so solvers can run concurrently
+/// against different inputs.
+pub fn with_input<T>(context: InputContext, f: impl FnOnce() -> T) -> T {
+    let _restore = RestoreInput(CURRENT_INPUT.replace(Some(context)));
+    f()
+}
+
+/// Apply `f` to the input context of the current thread, which defaults to
+/// the input files designated by the environment.
+pub(crate) fn current<T>(f: impl FnOnce(&InputContext) -> T) -> T {
+    CURRENT_INPUT.with_borrow(|context| match context {
+        Some(context) => f(context),
+        None => f(&InputContext::default()),
+    })
+}
+
+/// Check whether solvers on the current thread run an example, in which
+/// case they receive the values of `example_param(…)`.
+#[must_use]
+pub fn is_example() -> bool {
+    current(|context| context.example.is_some())
+}
+
+/// Normalisation applied to an input before giving it to a solver, chosen
+/// with `normalize = raw`, `lines` or `trim` in the `#[aoc]` attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// The input is given as is.
+    Raw,
+    /// CRLF line endings are converted to LF, a UTF-8 byte order mark is
+    /// removed, and a non-empty input ends with exactly one newline.
+    #[default]
+    Lines,
+    /// Same as `Lines`, then leading and trailing whitespace, including the
+    /// final newline, is removed.
+    Trim,
+}
+
+impl Normalization {
+    /// Normalise an input.
+    #[must_use]
+    pub fn apply(self, input: Vec<u8>) -> Vec<u8> {
+        if self == Normalization::Raw {
+            return input;
+        }
+        let input = input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&input);
+        let mut normalized = Vec::with_capacity(input.len() + 1);
+        for (i, &b) in input.iter().enumerate() {
+            if !(b == b'\r' && input.get(i + 1) == Some(&b'\n')) {
+                normalized.push(b);
+            }
+        }
+        if self == Normalization::Trim {
+            return normalized.trim_ascii().to_vec();
+        }
+        while normalized.last() == Some(&b'\n') {
+            normalized.pop();
+        }
+        if !normalized.is_empty() {
+            normalized.push(b'\n');
+        }
+        normalized
+    }
+}
+
+/// Input of a day, with the default normalisation.
+pub fn input_bytes(day: usize) -> Result<Vec<u8>, Error> {
+    input_bytes_with(day, Normalization::default())
+}
+
+/// Input of a day, with the given normalisation.
+pub fn input_bytes_with(day: usize, normalization: Normalization) -> Result<Vec<u8>, Error> {
+    Ok(normalization.apply(current(|context| context.input_bytes(day))?))
+}
+
+/// Input of a day as a string, with the default normalisation.
 pub fn input_string(day: usize) -> Result<String, Error> {
-    Ok(String::from_utf8(input_bytes(day)?)?)
+    input_string_with(day, Normalization::default())
+}
+
+/// Input of a day as a string, with the given normalisation.
+pub fn input_string_with(day: usize, normalization: Normalization) -> Result<String, Error> {
+    Ok(String::from_utf8(input_bytes_with(day, normalization)?)?)
 }
 
 /// Parse input as `lines()` if `sep` is absent, or as a single line
-/// if `sep` is present.
+/// without surrounding whitespace if `sep` is present. An item which cannot
+/// be parsed gives an [`Error::Parse`] locating it in the input.
 pub fn parse_input<T>(input: &str, sep: Option<&str>) -> Result<Vec<T>, Error>
 where
     T: FromStr,
     <T as FromStr>::Err: Into<eyre::Report>,
 {
+    locate_parse_errors(None, input, sep)
+}
+
+/// Parse the input of a day as [`parse_input`] does, an [`Error::Parse`]
+/// also telling the day.
+pub fn parse_day_input<T>(day: usize, input: &str, sep: Option<&str>) -> Result<Vec<T>, Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Into<eyre::Report>,
+{
+    locate_parse_errors(Some(day), input, sep)
+}
+
+fn locate_parse_errors<T>(
+    day: Option<usize>,
+    input: &str,
+    sep: Option<&str>,
+) -> Result<Vec<T>, Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Into<eyre::Report>,
+{
+    let parse = |item: &str| {
+        item.parse().map_err(|e: <T as FromStr>::Err| {
+            // Items are slices of the input, which locates them
+            let offset = item.as_ptr() as usize - input.as_ptr() as usize;
+            let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+            Error::Parse {
+                day,
+                line: input[..offset].matches('\n').count() + 1,
+                column: input[line_start..offset].chars().count() + 1,
+                content: input[line_start..]
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned(),
+                source: e.into().into(),
+            }
+        })
+    };
     match sep {
-        Some(sep) => Ok(input
-            .trim()
-            .split(sep)
-            .map(|s| s.parse().map_err(Error::from_error))
-            .collect::<Result<Vec<T>, Error>>()?),
-        None => Ok(input
-            .lines()
-            .map(|s| s.parse().map_err(Error::from_error))
-            .collect::<Result<Vec<T>, _>>()?),
+        Some(sep) => input.trim().split(sep).map(parse).collect(),
+        None => input.lines().map(parse).collect(),
     }
 }
 
+/// Split input into lines if `sep` is absent, or as a single line without
+/// surrounding whitespace if `sep` is present, the same way as
+/// [`parse_input`].
 pub fn parse_input_bytes(input: &[u8], sep: Option<u8>) -> Result<Vec<&[u8]>, Error> {
-    let sep = sep.unwrap_or(b'\n');
-    Ok(input
-        .strip_suffix(&[sep])
-        .unwrap_or(input)
-        .split(|&b| b == sep)
-        .collect())
+    let (input, sep) = match sep {
+        Some(sep) => (input.trim_ascii(), sep),
+        None => (input.strip_suffix(b"\n").unwrap_or(input), b'\n'),
+    };
+    Ok(input.split(|&b| b == sep).collect())
 }
@@ -0,0 +1,283 @@
+use crate::input::{InputContext, InputSource};
+use crate::run::{AllocStats, Opts, Outcome, execute};
+use crate::runners::Registry;
+use chrono::Duration;
+use eyre::{Context, bail};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Marker starting the line which precedes the outcome of an isolated
+/// solver, so that anything the solver prints itself can be told apart.
+const OUTCOME_MARKER: &str = "\u{1e}aoc-isolated-outcome\u{1e}";
+
+/// Parse a memory size such as `2G`, `512M`, `64KiB` or `1000000`.
+pub(crate) fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (n, unit) = s.split_at(digits);
+    let n = n
+        .parse::<u64>()
+        .map_err(|_| format!("invalid memory size {s}"))?;
+    let shift = match unit
+        .trim_end_matches(['b', 'B'])
+        .trim_end_matches('i')
+        .to_ascii_uppercase()
+        .as_str()
+    {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(format!("invalid memory size unit in {s}")),
+    };
+    n.checked_mul(1 << shift)
+        .ok_or_else(|| format!("memory size {s} is too large"))
+}
+
+fn spec(day: usize, part: usize, version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("{day}/{part}/{version}"),
+        None => format!("{day}/{part}"),
+    }
+}
+
+/// Run one solver in a child process executing the current binary, and
+/// collect its answer, duration and peak resident set size.
+pub(crate) fn run_isolated(
+    day: usize,
+    part: usize,
+    version: Option<&str>,
+    opts: &Opts,
+    input: &InputContext,
+) -> eyre::Result<Outcome> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command.args(["--isolate", "--isolated-child", &spec(day, part, version)]);
+    let stdin = match input.source() {
+        Some(InputSource::File { path, .. }) => {
+            command.arg("--input").arg(path);
+            None
+        }
+        Some(InputSource::String(s)) => {
+            command.args(["--input-string", s]);
+            None
+        }
+        Some(source @ (InputSource::Stdin(_) | InputSource::Text(_))) => {
+            command.args(["--input", "-"]).stdin(Stdio::piped());
+            Some(source.content())
+        }
+        None => None,
+    };
+    if let Some(example) = input.example() {
+        command.args(["--example", &example.to_string()]);
+    }
+    if let Some(name) = input.name() {
+        command.args(["--input-name", name]);
+    }
+    if let Some(user) = &opts.user {
+        command.args(["--user", user]);
+    }
+    if let Some(input_dir) = &opts.input_dir {
+        command.arg("--input-dir").arg(input_dir);
+    }
+    if let Some(input_template) = &opts.input_template {
+        command.args(["--input-template", input_template]);
+    }
+    if let Some(max_memory) = opts.max_memory {
+        command.args(["--max-memory", &max_memory.to_string()]);
+    }
+    if let Some(max_cpu_time) = opts.max_cpu_time {
+        command.args(["--max-cpu-time", &max_cpu_time.to_string()]);
+    }
+    if opts.memory {
+        command.arg("--memory");
+    }
+    let output = match stdin {
+        Some(content) => {
+            let mut child = command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("cannot start isolated solver")?;
+            // The child reads its whole input before running the solver
+            child.stdin.take().unwrap().write_all(&content)?;
+            child.wait_with_output()?
+        }
+        None => command.output().context("cannot start isolated solver")?,
+    };
+    if !output.status.success() {
+        // Keep the lines preceding the notes or the backtrace, such as the
+        // location and message of a panic
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut message = stderr
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .take_while(|line| !line.starts_with("note:") && !line.starts_with("stack backtrace:"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        #[cfg(unix)]
+        if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&output.status) {
+            if !message.is_empty() {
+                message.push(' ');
+            }
+            write!(&mut message, "(killed by signal {signal})")?;
+        }
+        if message.is_empty() {
+            message = format!("solver exited with {}", output.status);
+        }
+        bail!(message);
+    }
+    let stdout = String::from_utf8(output.stdout)?;
+    let Some((printed, outcome)) = stdout.split_once(&format!("\n{OUTCOME_MARKER}")) else {
+        bail!("malformed output from isolated solver");
+    };
+    // Output of the solver itself is passed along
+    print!("{printed}");
+    parse_outcome(outcome)
+}
+
+/// Parse the outcome written by an isolated solver: a header line holding
+/// its measures, followed by its answer.
+fn parse_outcome(outcome: &str) -> eyre::Result<Outcome> {
+    let Some((header, answer)) = outcome.split_once('\n') else {
+        bail!("malformed output from isolated solver");
+    };
+    let mut outcome = Outcome {
+        answer: answer.to_owned(),
+        duration: Duration::zero(),
+        peak_rss: None,
+        alloc_stats: None,
+    };
+    for field in header.split_whitespace() {
+        match field.split_once('=') {
+            Some(("time", ns)) => outcome.duration = Duration::nanoseconds(ns.parse()?),
+            Some(("rss", bytes)) => outcome.peak_rss = Some(bytes.parse()?),
+            Some(("alloc", stats)) => {
+                let stats = stats
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<usize>, _>>()?;
+                let [allocations, allocated, peak] = stats[..] else {
+                    bail!("malformed allocation statistics from isolated solver");
+                };
+                outcome.alloc_stats = Some(AllocStats {
+                    allocations,
+                    allocated,
+                    peak,
+                });
+            }
+            _ => bail!("unknown field {field} in isolated solver output"),
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(unix)]
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+#[cfg(unix)]
+fn apply_limits(opts: &Opts) -> eyre::Result<()> {
+    if let Some(max_memory) = opts.max_memory
+        && unsafe { libc::setrlimit(libc::RLIMIT_AS, &rlimit(max_memory)) } != 0
+    {
+        bail!("cannot limit memory: {}", std::io::Error::last_os_error());
+    }
+    if let Some(max_cpu_time) = opts.max_cpu_time
+        && unsafe { libc::setrlimit(libc::RLIMIT_CPU, &rlimit(max_cpu_time)) } != 0
+    {
+        bail!("cannot limit CPU time: {}", std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply_limits(opts: &Opts) -> eyre::Result<()> {
+    if opts.max_memory.is_some() || opts.max_cpu_time.is_some() {
+        bail!("resource limits are not supported on this platform");
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn peak_rss() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let max_rss = u64::try_from(unsafe { usage.assume_init() }.ru_maxrss).ok()?;
+    // `ru_maxrss` is expressed in bytes on macOS and in kilobytes elsewhere.
+    if cfg!(target_os = "macos") {
+        Some(max_rss)
+    } else {
+        Some(max_rss * 1024)
+    }
+}
+
+#[cfg(not(unix))]
+fn peak_rss() -> Option<u64> {
+    None
+}
+
+fn run_child<F>(register: F, spec: &str, opts: &Opts) -> eyre::Result<String>
+where
+    F: Fn(),
+{
+    let mut fields = spec.splitn(3, '/');
+    let (Some(day), Some(part)) = (fields.next(), fields.next()) else {
+        bail!("malformed solver specification {spec}");
+    };
+    let (day, part) = (day.parse::<usize>()?, part.parse::<usize>()?);
+    let version = fields.next();
+    let registry = Registry::collect(register);
+    let context = InputContext::from_opts(opts)?;
+    apply_limits(opts)?;
+    let Some((_, runner)) = registry
+        .runners
+        .get(&(day, part))
+        .and_then(|versions| versions.iter().find(|(v, _)| v.as_deref() == version))
+    else {
+        bail!("no solver registered for {spec}");
+    };
+    let outcome = super::input::with_input(context, || execute(runner.as_ref(), opts))?;
+    let mut header = format!(
+        "time={}",
+        outcome.duration.num_nanoseconds().unwrap_or(i64::MAX)
+    );
+    if let Some(rss) = peak_rss() {
+        write!(&mut header, " rss={rss}")?;
+    }
+    if let Some(stats) = outcome.alloc_stats {
+        write!(
+            &mut header,
+            " alloc={},{},{}",
+            stats.allocations, stats.allocated, stats.peak
+        )?;
+    }
+    Ok(format!("\n{OUTCOME_MARKER}{header}\n{}", outcome.answer))
+}
+
+/// Entry point of the child process: run the designated solver and write
+/// its outcome on the standard output, or its error on the standard error.
+pub(crate) fn child_main<F>(register: F, spec: &str, opts: &Opts) -> !
+where
+    F: Fn(),
+{
+    match run_child(register, spec, opts) {
+        Ok(output) => {
+            print!("{output}");
+            let _ = std::io::stdout().flush();
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{e:#}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -3,11 +3,21 @@
 
 pub use aoc_derive::*;
 
+#[cfg(feature = "memory")]
+mod alloc;
+mod answers;
 pub mod error;
+mod expected;
+mod hash;
 pub mod input;
+mod isolate;
+mod output;
+mod record;
+mod report;
 mod run;
 mod runners;
+mod selection;
 pub mod test;
 
-pub use run::run;
-pub use runners::register_runner;
+pub use run::{run, solve};
+pub use runners::{register_answer, register_crate, register_runner};
@@ -0,0 +1,232 @@
+use crate::run::{Opts, Outcome, SolverResult};
+use chrono::Duration;
+use std::fmt::Write;
+
+/// Format used to print the results of a run.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub(crate) enum Format {
+    /// Aligned human-readable text
+    #[default]
+    Text,
+    /// JSON array with one object per solver
+    Json,
+    /// Comma separated values with a header line
+    Csv,
+    /// Markdown table
+    Markdown,
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn pretty_duration(duration: Duration) -> String {
+    if duration < Duration::microseconds(1) {
+        format!("{} ns", duration.num_nanoseconds().unwrap())
+    } else if duration < Duration::milliseconds(1) {
+        format!(
+            "{:.2} µs",
+            duration.num_nanoseconds().unwrap() as f32 / 1000.0
+        )
+    } else if duration < Duration::seconds(1) {
+        format!(
+            "{:.2} ms",
+            duration.num_microseconds().unwrap() as f32 / 1000.0
+        )
+    } else {
+        format!("{:.2} s", duration.num_milliseconds() as f32 / 1000.0)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn pretty_size(bytes: u64) -> String {
+    if bytes < 1 << 10 {
+        format!("{bytes} B")
+    } else if bytes < 1 << 20 {
+        format!("{:.2} KiB", bytes as f32 / 1024.0)
+    } else if bytes < 1 << 30 {
+        format!("{:.2} MiB", bytes as f32 / (1 << 20) as f32)
+    } else {
+        format!("{:.2} GiB", bytes as f32 / (1 << 30) as f32)
+    }
+}
+
+fn measures(outcome: &Outcome, opts: &Opts) -> Vec<String> {
+    let mut measures = Vec::new();
+    if opts.timing {
+        measures.push(pretty_duration(outcome.duration));
+        if let Some(peak_rss) = outcome.peak_rss {
+            measures.push(format!("peak RSS {}", pretty_size(peak_rss)));
+        }
+    }
+    if opts.memory
+        && let Some(stats) = outcome.alloc_stats
+    {
+        measures.push(format!(
+            "{} allocations, {} allocated, peak heap {}",
+            stats.allocations,
+            pretty_size(stats.allocated as u64),
+            pretty_size(stats.peak as u64)
+        ));
+    }
+    measures
+}
+
+fn title(result: &SolverResult) -> String {
+    let mut title = format!("Day {} - part {}", result.day, result.part);
+    if let Some(version) = &result.version {
+        write!(&mut title, " — {version}").unwrap();
+    }
+    title
+}
+
+fn text(results: &[SolverResult], opts: &Opts) -> eyre::Result<String> {
+    let mut output = String::new();
+    for result in results {
+        let output_start = output.chars().count();
+        write!(&mut output, "{}", title(result))?;
+        if result.differs {
+            write!(&mut output, " [differs from main]")?;
+        }
+        if let Ok(outcome) = &result.outcome {
+            let measures = measures(outcome, opts);
+            if !measures.is_empty() {
+                write!(&mut output, " ({})", measures.join(", "))?;
+            }
+        }
+        write!(&mut output, ": ")?;
+        let sep = format!("\n{}", " ".repeat(output.chars().count() - output_start));
+        write!(&mut output, "{}", result.answer().replace('\n', &sep))?;
+        match result.is_correct(opts.salt.as_deref()) {
+            Some(true) => writeln!(&mut output, " ✓")?,
+            Some(false) => writeln!(&mut output, " ✗")?,
+            None => writeln!(&mut output)?,
+        }
+    }
+    Ok(output)
+}
+
+fn json(results: &[SolverResult]) -> eyre::Result<String> {
+    let objects = results
+        .iter()
+        .map(|result| {
+            let (answer, error) = match &result.outcome {
+                Ok(outcome) => (Some(outcome.answer.trim()), None),
+                Err(e) => (None, Some(e)),
+            };
+            let outcome = result.outcome.as_ref().ok();
+            let alloc_stats = outcome.and_then(|o| o.alloc_stats);
+            serde_json::json!({
+                "day": result.day,
+                "part": result.part,
+                "variant": result.version,
+                "answer": answer,
+                "time_ns": outcome.and_then(|o| o.duration.num_nanoseconds()),
+                "peak_rss": outcome.and_then(|o| o.peak_rss),
+                "allocations": alloc_stats.map(|s| s.allocations),
+                "allocated": alloc_stats.map(|s| s.allocated),
+                "peak_heap": alloc_stats.map(|s| s.peak),
+                "differs_from_main": result.differs,
+                "error": error,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::to_string_pretty(&objects)? + "\n")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn csv(results: &[SolverResult]) -> eyre::Result<String> {
+    let mut output = String::from(
+        "day,part,variant,answer,time_ns,peak_rss,allocations,allocated,peak_heap,differs_from_main,error\n",
+    );
+    let opt = |n: Option<String>| n.unwrap_or_default();
+    for result in results {
+        let outcome = result.outcome.as_ref().ok();
+        let alloc_stats = outcome.and_then(|o| o.alloc_stats);
+        let fields = [
+            result.day.to_string(),
+            result.part.to_string(),
+            opt(result.version.clone()),
+            opt(outcome.map(|o| o.answer.trim().to_owned())),
+            opt(outcome.and_then(|o| o.duration.num_nanoseconds().map(|n| n.to_string()))),
+            opt(outcome.and_then(|o| o.peak_rss.map(|n| n.to_string()))),
+            opt(alloc_stats.map(|s| s.allocations.to_string())),
+            opt(alloc_stats.map(|s| s.allocated.to_string())),
+            opt(alloc_stats.map(|s| s.peak.to_string())),
+            result.differs.to_string(),
+            opt(result.outcome.as_ref().err().cloned()),
+        ];
+        writeln!(
+            &mut output,
+            "{}",
+            fields
+                .iter()
+                .map(|f| csv_field(f))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+    }
+    Ok(output)
+}
+
+fn markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn markdown(results: &[SolverResult], opts: &Opts) -> eyre::Result<String> {
+    let with_measures = opts.timing || opts.memory;
+    let mut output = String::from("| Day | Part | Variant | Answer |");
+    if with_measures {
+        output.push_str(" Measures |");
+    }
+    output.push_str("\n|----:|-----:|---------|--------|");
+    if with_measures {
+        output.push_str("----------|");
+    }
+    output.push('\n');
+    for result in results {
+        let mut answer = match &result.outcome {
+            Ok(_) => result
+                .answer()
+                .lines()
+                .map(|l| format!("`{l}`"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(e) => e.clone(),
+        };
+        if result.differs {
+            answer.push_str(" (differs from main)");
+        }
+        write!(
+            &mut output,
+            "| {} | {} | {} | {} |",
+            result.day,
+            result.part,
+            result.version.as_deref().unwrap_or("main"),
+            markdown_cell(&answer)
+        )?;
+        if with_measures {
+            let measures = result
+                .outcome
+                .as_ref()
+                .map(|outcome| measures(outcome, opts).join(", "))
+                .unwrap_or_default();
+            write!(&mut output, " {} |", markdown_cell(&measures))?;
+        }
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+pub(crate) fn render(results: &[SolverResult], opts: &Opts) -> eyre::Result<String> {
+    match opts.format {
+        Format::Text => text(results, opts),
+        Format::Json => json(results),
+        Format::Csv => csv(results),
+        Format::Markdown => markdown(results, opts),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::expected::Key;
+use crate::hash;
+use crate::run::SolverResult;
+use eyre::{Context, bail};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Expected results to update when recording, written as a comma separated
+/// list of days and parts such as `day3,day5p2`. `1` or `all` select every
+/// result.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecordEntries(Option<Vec<(usize, Option<usize>)>>);
+
+impl RecordEntries {
+    /// Entries selecting every result.
+    #[must_use]
+    pub fn all() -> Self {
+        RecordEntries(None)
+    }
+
+    fn contains(&self, day: usize, part: usize) -> bool {
+        self.0.as_ref().is_none_or(|entries| {
+            entries
+                .iter()
+                .any(|&(d, p)| d == day && p.is_none_or(|p| p == part))
+        })
+    }
+
+    /// Days containing the selected entries, or `None` if every day is
+    /// selected.
+    pub(crate) fn days(&self) -> Option<impl Iterator<Item = usize> + '_> {
+        self.0
+            .as_ref()
+            .map(|entries| entries.iter().map(|&(day, _)| day))
+    }
+}
+
+impl FromStr for RecordEntries {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if matches!(s.trim(), "" | "1" | "all") {
+            return Ok(RecordEntries::all());
+        }
+        s.split(',')
+            .map(|entry| {
+                let err = || format!("invalid entry {entry:?} (`dayN` or `dayNpP` expected)");
+                let rest = entry.trim().strip_prefix("day").ok_or_else(err)?;
+                match rest.split_once('p') {
+                    Some((day, part)) => Ok((
+                        day.parse().map_err(|_| err())?,
+                        Some(part.parse().map_err(|_| err())?),
+                    )),
+                    None => Ok((rest.parse().map_err(|_| err())?, None)),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(|entries| RecordEntries(Some(entries)))
+    }
+}
+
+/// Render expected results in the format produced by a text run. Alternate
+/// versions are marked as differing from the main one if
+/// `variants_follow_main` is set.
+fn render<'a>(
+    entries: impl IntoIterator<Item = (&'a Key, &'a str)> + Clone,
+    variants_follow_main: bool,
+) -> eyre::Result<String> {
+    let main = entries
+        .clone()
+        .into_iter()
+        .filter(|((_, _, version), _)| version.is_none())
+        .map(|((day, part, _), answer)| ((*day, *part), answer))
+        .collect::<BTreeMap<_, _>>();
+    let mut output = String::new();
+    for ((day, part, version), answer) in entries {
+        let mut header = format!("Day {day} - part {part}");
+        if let Some(version) = version {
+            write!(&mut header, " — {version}")?;
+            if variants_follow_main && main.get(&(*day, *part)) != Some(&answer) {
+                write!(&mut header, " [differs from main]")?;
+            }
+        }
+        let sep = format!("\n{}", " ".repeat(header.chars().count() + 2));
+        writeln!(&mut output, "{header}: {}", answer.replace('\n', &sep))?;
+    }
+    Ok(output)
+}
+
+fn title(result: &SolverResult) -> String {
+    let mut title = format!("day {} part {}", result.day, result.part);
+    if let Some(version) = &result.version {
+        write!(&mut title, " ({version})").unwrap();
+    }
+    title
+}
+
+/// Describe a failing solver whose answer has not been recorded.
+pub(crate) fn not_recorded(result: &SolverResult) -> String {
+    format!("{}: not recorded, {}", title(result), result.answer())
+}
+
+/// How to record results in an expected file.
+pub(crate) struct Recording<'a> {
+    /// Results to record
+    pub(crate) entries: &'a RecordEntries,
+    /// Overwrite existing answers with different ones
+    pub(crate) force: bool,
+    /// Key used to store hashes of the answers instead of the answers
+    pub(crate) salt: Option<&'a str>,
+}
+
+/// Record the selected results in the expected file, refusing to overwrite
+/// an existing answer with a different one unless forced to. If the
+/// results come from a `complete` run and every entry is selected, stale
+/// entries are removed from a text file. Failing solvers are not recorded,
+/// and are returned.
+pub(crate) fn record<'a>(
+    results: &'a [SolverResult],
+    path: &Path,
+    recording: &Recording,
+    variants_follow_main: bool,
+    complete: bool,
+) -> eyre::Result<Vec<&'a SolverResult>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).context(format!("cannot read {}", path.display())),
+    };
+    let mut expected = super::expected::read(path)?;
+    let (results, failures): (Vec<_>, Vec<_>) = results
+        .iter()
+        .filter(|r| recording.entries.contains(r.day, r.part))
+        .partition(|r| r.outcome.is_ok());
+    if !recording.force {
+        let conflicts = results
+            .iter()
+            .filter_map(|r| {
+                let previous = expected.get(&(r.day, r.part, r.version.clone()))?;
+                (!hash::matches(recording.salt, previous, r.answer()))
+                    .then(|| format!("{}: {previous} would become {}", title(r), r.answer()))
+            })
+            .collect::<Vec<_>>();
+        if !conflicts.is_empty() {
+            bail!(
+                "refusing to overwrite expected answers in {}, use --force or set {}=1 to do so:\n{}",
+                path.display(),
+                super::test::FORCE_ENV_VAR,
+                conflicts.join("\n")
+            );
+        }
+    }
+    // Matching answers are kept as they are, unless they must now be hashed
+    let answers = results
+        .iter()
+        .map(|r| {
+            let key = (r.day, r.part, r.version.clone());
+            let answer = match (expected.get(&key), recording.salt) {
+                (Some(previous), salt)
+                    if hash::matches(salt, previous, r.answer())
+                        && (salt.is_none() || hash::is_hashed(previous)) =>
+                {
+                    previous.clone()
+                }
+                (_, Some(key)) => hash::hash(key, r.answer()),
+                (_, None) => r.answer().to_owned(),
+            };
+            (key, answer)
+        })
+        .collect::<Vec<_>>();
+    let recorded = if super::expected::is_structured(path) {
+        super::answers::record(&content, &answers)?
+    } else if complete && failures.is_empty() && recording.entries.0.is_none() {
+        render(
+            answers.iter().map(|(key, answer)| (key, answer.as_str())),
+            variants_follow_main,
+        )?
+    } else {
+        expected.extend(answers);
+        render(
+            expected.iter().map(|(key, answer)| (key, answer.as_str())),
+            variants_follow_main,
+        )?
+    };
+    if recorded != content {
+        std::fs::write(path, recorded)?;
+    }
+    Ok(failures)
+}
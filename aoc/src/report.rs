@@ -0,0 +1,197 @@
+use crate::expected::Key;
+use crate::hash;
+use crate::run::SolverResult;
+use crate::test::CheckOptions;
+use eyre::bail;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Format of a test report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// `JUnit` XML, as understood by most CI dashboards
+    JUnit,
+    /// Test Anything Protocol, version 13
+    Tap,
+}
+
+/// Test report to write when checking results, with one test case per
+/// solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for Report {
+    type Err = eyre::Report;
+
+    /// Parse a report description such as `junit:target/aoc.xml` or
+    /// `tap:target/aoc.tap`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((format, path)) = s.split_once(':') else {
+            bail!("report must be given as FORMAT:PATH");
+        };
+        let format = match format {
+            "junit" => ReportFormat::JUnit,
+            "tap" => ReportFormat::Tap,
+            _ => bail!("unknown report format {format} (junit or tap expected)"),
+        };
+        Ok(Report {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Outcome of checking one solver against its expected answer.
+struct TestCase<'a> {
+    result: &'a SolverResult,
+    expected: Option<&'a str>,
+    key: Option<&'a str>,
+}
+
+impl TestCase<'_> {
+    fn name(&self) -> String {
+        format!(
+            "day{}::part{}::{}",
+            self.result.day,
+            self.result.part,
+            self.result.version.as_deref().unwrap_or("main")
+        )
+    }
+
+    fn seconds(&self) -> f64 {
+        self.result.outcome.as_ref().map_or(0.0, |o| {
+            o.duration
+                .to_std()
+                .map_or(0.0, |duration| duration.as_secs_f64())
+        })
+    }
+
+    fn failure(&self) -> Option<String> {
+        match (&self.result.outcome, self.expected) {
+            (Err(e), _) => Some(e.clone()),
+            (Ok(_), None) => Some(String::from("no expected answer")),
+            (Ok(_), Some(expected)) if !hash::matches(self.key, expected, self.result.answer()) => {
+                Some(format!("expected {expected}, got {}", self.result.answer()))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn junit(cases: &[TestCase]) -> eyre::Result<String> {
+    let failures = cases.iter().filter(|c| c.failure().is_some()).count();
+    let time = cases.iter().map(TestCase::seconds).sum::<f64>();
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        &mut output,
+        "<testsuites tests=\"{}\" failures=\"{failures}\" time=\"{time:.6}\">",
+        cases.len()
+    )?;
+    writeln!(
+        &mut output,
+        "  <testsuite name=\"aoc\" tests=\"{}\" failures=\"{failures}\" time=\"{time:.6}\">",
+        cases.len()
+    )?;
+    for case in cases {
+        let result = case.result;
+        write!(
+            &mut output,
+            "    <testcase classname=\"day{}.part{}\" name=\"{}\" time=\"{:.6}\"",
+            result.day,
+            result.part,
+            xml_escape(result.version.as_deref().unwrap_or("main")),
+            case.seconds()
+        )?;
+        match case.failure() {
+            Some(failure) => {
+                writeln!(&mut output, ">")?;
+                writeln!(
+                    &mut output,
+                    "      <failure message=\"{}\">expected:\n{}\nactual:\n{}</failure>",
+                    xml_escape(&failure),
+                    xml_escape(case.expected.unwrap_or_default()),
+                    xml_escape(result.answer())
+                )?;
+                writeln!(&mut output, "    </testcase>")?;
+            }
+            None => writeln!(&mut output, "/>")?,
+        }
+    }
+    writeln!(&mut output, "  </testsuite>")?;
+    writeln!(&mut output, "</testsuites>")?;
+    Ok(output)
+}
+
+fn yaml_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+fn tap(cases: &[TestCase]) -> eyre::Result<String> {
+    let mut output = format!("TAP version 13\n1..{}\n", cases.len());
+    for (i, case) in cases.iter().enumerate() {
+        let failure = case.failure();
+        let status = if failure.is_some() { "not ok" } else { "ok" };
+        writeln!(&mut output, "{status} {} - {}", i + 1, case.name())?;
+        writeln!(&mut output, "  ---")?;
+        if let Some(failure) = failure {
+            writeln!(&mut output, "  message: {}", yaml_string(&failure))?;
+            if let Some(expected) = case.expected {
+                writeln!(&mut output, "  expected: {}", yaml_string(expected))?;
+            }
+            writeln!(
+                &mut output,
+                "  actual: {}",
+                yaml_string(case.result.answer())
+            )?;
+        }
+        writeln!(&mut output, "  duration_ms: {:.3}", case.seconds() * 1000.0)?;
+        writeln!(&mut output, "  ...")?;
+    }
+    Ok(output)
+}
+
+/// Write a report comparing every result with its expected answer.
+pub(crate) fn write(
+    report: &Report,
+    results: &[SolverResult],
+    expected: &BTreeMap<Key, String>,
+    options: &CheckOptions,
+) -> eyre::Result<()> {
+    let key = hash::key(options.salt.as_deref());
+    let cases = results
+        .iter()
+        .map(|result| TestCase {
+            result,
+            expected: super::expected::lookup(
+                expected,
+                result.day,
+                result.part,
+                result.version.as_deref(),
+                options.verify_variants,
+            )
+            .or(result.declared.as_deref()),
+            key: key.as_deref(),
+        })
+        .collect::<Vec<_>>();
+    let content = match report.format {
+        ReportFormat::JUnit => junit(&cases)?,
+        ReportFormat::Tap => tap(&cases)?,
+    };
+    if let Some(parent) = report.path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&report.path, content)?;
+    Ok(())
+}
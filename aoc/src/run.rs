@@ -1,128 +1,509 @@
 use chrono::{Datelike, Duration};
 use clap::Parser;
-use std::fmt::Write;
+use std::path::PathBuf;
 
-#[derive(Parser)]
+use crate::error::Error;
+use crate::input::{InputContext, InputSource};
+use crate::output::Format;
+use crate::record::{RecordEntries, Recording};
+use crate::runners::{Registry, Runner};
+use crate::selection::Selection;
+
+#[derive(Parser, Default)]
 #[clap(version, author)]
+#[allow(clippy::struct_excessive_bools)]
 /// Advent of Code
-struct Opts {
+pub(crate) struct Opts {
     #[clap(short, long)]
-    /// Run all days
-    all: bool,
+    /// Run all days (same as `--day 1-`)
+    pub(crate) all: bool,
 
-    #[clap(short, long)]
-    /// Use a specific day
-    day: Option<usize>,
+    #[clap(short, long, value_name = "DAYS")]
+    /// Use specific days (e.g. `3` or `1-5,8,12-`)
+    pub(crate) day: Option<Selection>,
 
-    #[clap(short, long)]
-    /// Restrict running to one part (1 or 2)
-    part: Option<usize>,
+    #[clap(short, long, value_name = "PARTS")]
+    /// Restrict running to some parts (e.g. `1` or `1,2`)
+    pub(crate) part: Option<Selection>,
 
     #[clap(short, long)]
     /// Show timing information
-    timing: bool,
+    pub(crate) timing: bool,
 
     #[clap(short, long)]
     /// Skip running any alternate version
-    main_only: bool,
+    pub(crate) main_only: bool,
 
-    #[clap(short, long)]
-    /// Use alternate input (file or string)
-    input: Option<String>,
+    #[clap(long = "variant", value_name = "PATTERN")]
+    /// Run only versions whose name matches the pattern (`main` for the main version)
+    pub(crate) variants: Vec<glob::Pattern>,
+
+    #[clap(long = "exclude-variant", value_name = "PATTERN")]
+    /// Skip versions whose name matches the pattern (`main` for the main version)
+    pub(crate) excluded_variants: Vec<glob::Pattern>,
+
+    #[clap(short, long, value_name = "FILE")]
+    /// Use alternate input file (`-` for the standard input)
+    pub(crate) input: Option<String>,
+
+    #[clap(long, value_name = "TEXT", conflicts_with = "input")]
+    /// Use the given text as input
+    pub(crate) input_string: Option<String>,
+
+    #[clap(long, value_name = "N", num_args = 0..=1, default_missing_value = "1", conflicts_with_all = ["input", "input_string"])]
+    /// Use the example inputs `dayD.example.txt` or `dayD/exampleN.txt` of the input directory
+    pub(crate) example: Option<usize>,
+
+    #[clap(long, conflicts_with_all = ["input", "input_string", "example"])]
+    /// Also run every day on each additional input `dayD/NAME.txt` of the input directory
+    pub(crate) all_inputs: bool,
+
+    #[clap(long, hide = true)]
+    pub(crate) input_name: Option<String>,
+
+    #[clap(long, value_name = "NAME")]
+    /// Use the inputs of a user in `input/NAME` and their expected answers in `expected-user-NAME.txt` [default: `$AOC_USER`]
+    pub(crate) user: Option<String>,
+
+    #[clap(long, value_name = "DIR")]
+    /// Directory containing the input files [default: `$AOC_INPUT_DIR` or `input`]
+    pub(crate) input_dir: Option<PathBuf>,
+
+    #[clap(long, value_name = "TEMPLATE")]
+    /// Name of the input files, such as `{year}/day{day:02}.txt` [default: `$AOC_INPUT_TEMPLATE` or `day{day}.txt`]
+    pub(crate) input_template: Option<String>,
+
+    #[clap(long)]
+    /// Run every solver in its own child process
+    pub(crate) isolate: bool,
+
+    #[clap(long, requires = "isolate", value_parser = super::isolate::parse_size)]
+    /// Limit the memory of isolated solvers (e.g. 512M or 2G)
+    pub(crate) max_memory: Option<u64>,
+
+    #[clap(long, requires = "isolate")]
+    /// Limit the CPU time of isolated solvers, in seconds
+    pub(crate) max_cpu_time: Option<u64>,
+
+    #[clap(long)]
+    /// Report memory allocations (requires the `memory` feature of `aoc`)
+    pub(crate) memory: bool,
+
+    #[clap(long)]
+    /// Run every alternate version and report those differing from the main one
+    pub(crate) verify_variants: bool,
+
+    #[clap(long, value_enum, default_value_t)]
+    /// Output format
+    pub(crate) format: Format,
+
+    #[clap(long, value_name = "ENTRIES", num_args = 0..=1, default_missing_value = "all")]
+    /// Record the answers as expected results (all of them, or e.g. `day3,day5p2`)
+    pub(crate) record: Option<RecordEntries>,
+
+    #[clap(long, requires = "record")]
+    /// Allow recording to overwrite an expected answer with a different one
+    pub(crate) force: bool,
+
+    #[clap(long, env = "AOC_ANSWER_SALT", hide_env_values = true)]
+    /// Secret key used to record keyed hashes of the answers instead of the answers themselves, and to check hashed answers
+    pub(crate) salt: Option<String>,
+
+    #[clap(long, value_name = "FILE")]
+    /// Expected results file used when recording, checking or hiding answers, `expected-exampleN.txt` or `expected-NAME.txt` being used for examples and additional inputs [default: `expected.txt`]
+    pub(crate) expected: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Only show whether answers match the expected ones
+    pub(crate) hide_answers: bool,
+
+    #[clap(long, hide = true)]
+    pub(crate) isolated_child: Option<String>,
+
+    #[clap(skip)]
+    /// Report the errors of solvers as their answers instead of stopping
+    pub(crate) keep_going: bool,
 }
 
-#[allow(clippy::cast_precision_loss)]
-fn pretty_duration(duration: Duration) -> String {
-    if duration < Duration::microseconds(1) {
-        format!("{} ns", duration.num_nanoseconds().unwrap())
-    } else if duration < Duration::milliseconds(1) {
-        format!(
-            "{:.2} µs",
-            duration.num_nanoseconds().unwrap() as f32 / 1000.0
-        )
-    } else if duration < Duration::seconds(1) {
-        format!(
-            "{:.2} ms",
-            duration.num_microseconds().unwrap() as f32 / 1000.0
-        )
+impl Opts {
+    /// Expected results file of the input being used.
+    pub(crate) fn expected_file(&self, input: &InputContext) -> eyre::Result<PathBuf> {
+        // Like the input directory, the default file belongs to the crate
+        let mut path =
+            self.expected
+                .clone()
+                .unwrap_or_else(|| match std::env::var_os("CARGO_MANIFEST_DIR") {
+                    Some(manifest_dir) => PathBuf::from(manifest_dir).join("expected.txt"),
+                    None => PathBuf::from("expected.txt"),
+                });
+        if let Some(user) = input.user()? {
+            path = super::expected::for_user(&path, &user);
+        }
+        if let Some(label) = input.label() {
+            path = super::expected::for_input(&path, &label);
+        }
+        Ok(path)
+    }
+
+    /// Check whether a version is selected by the variant patterns.
+    pub(crate) fn selects(&self, version: Option<&str>) -> bool {
+        let name = version.unwrap_or("main");
+        (self.variants.is_empty() || self.variants.iter().any(|p| p.matches(name)))
+            && !self.excluded_variants.iter().any(|p| p.matches(name))
+    }
+}
+
+/// Allocations made while running a solver.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AllocStats {
+    pub(crate) allocations: usize,
+    pub(crate) allocated: usize,
+    pub(crate) peak: usize,
+}
+
+/// Answer and measurements of a single solver execution.
+pub(crate) struct Outcome {
+    pub(crate) answer: String,
+    pub(crate) duration: Duration,
+    pub(crate) peak_rss: Option<u64>,
+    pub(crate) alloc_stats: Option<AllocStats>,
+}
+
+#[cfg(feature = "memory")]
+fn count_allocations(runner: &Runner) -> (eyre::Result<String>, Option<AllocStats>) {
+    let (answer, stats) = super::alloc::measure(runner);
+    (answer, Some(stats))
+}
+
+#[cfg(not(feature = "memory"))]
+fn count_allocations(runner: &Runner) -> (eyre::Result<String>, Option<AllocStats>) {
+    (runner(), None)
+}
+
+/// Run a solver in the current process, timing it and counting its
+/// allocations if requested.
+pub(crate) fn execute(runner: &Runner, opts: &Opts) -> eyre::Result<Outcome> {
+    let before = chrono::Utc::now();
+    let (answer, alloc_stats) = if opts.memory {
+        count_allocations(runner)
     } else {
-        format!("{:.2} s", duration.num_milliseconds() as f32 / 1000.0)
+        (runner(), None)
+    };
+    let after = chrono::Utc::now();
+    Ok(Outcome {
+        answer: answer?,
+        duration: after - before,
+        peak_rss: None,
+        alloc_stats,
+    })
+}
+
+/// Result of running one version of a solver.
+pub(crate) struct SolverResult {
+    pub(crate) day: usize,
+    pub(crate) part: usize,
+    pub(crate) version: Option<String>,
+    pub(crate) outcome: Result<Outcome, String>,
+    /// Whether the answer differs from the one of the main version
+    pub(crate) differs: bool,
+    /// Answer declared in the `#[aoc]` attribute of the solver, or in the
+    /// example answers file when running examples
+    pub(crate) declared: Option<String>,
+}
+
+impl SolverResult {
+    /// Answer, or error message if the solver failed.
+    pub(crate) fn answer(&self) -> &str {
+        match &self.outcome {
+            Ok(outcome) => outcome.answer.trim(),
+            Err(e) => e,
+        }
+    }
+
+    /// Whether the answer matches the declared one, if any, hashed answers
+    /// being checked with the given key.
+    pub(crate) fn is_correct(&self, key: Option<&str>) -> Option<bool> {
+        self.declared.as_deref().map(|declared| {
+            self.outcome.is_ok() && super::hash::matches(key, declared, self.answer())
+        })
     }
 }
 
-#[allow(clippy::module_name_repetitions)]
-pub fn run_tests<F>(
-    register: F,
-    single_day: Option<usize>,
-    single_part: Option<usize>,
-    main_only: bool,
-    timings: bool,
-) -> eyre::Result<String>
-where
-    F: Fn(),
-{
-    register();
-    let mut results = String::new();
-    let mut runners = super::runners::RUNNERS.lock().unwrap();
-    let keys = runners.keys().copied().collect::<Vec<_>>();
-    for (day, part) in keys {
-        if single_part.is_some_and(|p| p != part) {
+pub(crate) fn run_solvers(
+    registry: &Registry,
+    opts: &Opts,
+    context: &InputContext,
+) -> eyre::Result<Vec<SolverResult>> {
+    let mut results = Vec::new();
+    for (&(day, part), versions) in &registry.runners {
+        if opts.part.as_ref().is_some_and(|p| !p.contains(part)) {
             continue;
         }
-        if single_day.is_none_or(|d| d == day) {
-            for (version, runner) in runners.remove(&(day, part)).unwrap() {
-                if main_only && version.is_some() {
+        // Days without the example or additional input being run are skipped
+        if context.skips(day) {
+            continue;
+        }
+        if opts.day.as_ref().is_none_or(|d| d.contains(day)) {
+            let mut day_results = Vec::new();
+            for (version, runner) in versions {
+                // The main version is always needed to verify the others
+                let needed = if opts.verify_variants {
+                    version.is_none() || opts.selects(version.as_deref())
+                } else {
+                    opts.selects(version.as_deref()) && !(opts.main_only && version.is_some())
+                };
+                if !needed {
                     continue;
                 }
-                let results_start = results.chars().count();
-                write!(&mut results, "Day {day} - part {part}")?;
-                if let Some(version) = version {
-                    write!(&mut results, " — {version}")?;
+                let outcome = if opts.isolate {
+                    super::isolate::run_isolated(day, part, version.as_deref(), opts, context)
+                } else {
+                    let outcome = super::input::with_input(context.clone(), || {
+                        execute(runner.as_ref(), opts)
+                    });
+                    if opts.keep_going {
+                        outcome
+                    } else {
+                        Ok(outcome?)
+                    }
                 }
-                let before = chrono::Utc::now();
-                let result = runner()?;
-                let after = chrono::Utc::now();
-                if timings {
-                    write!(&mut results, " ({})", pretty_duration(after - before))?;
+                // An isolated solver failing must not prevent the others from running
+                .map_err(|e| format!("error: {e:#}"));
+                day_results.push(SolverResult {
+                    day,
+                    part,
+                    version: version.clone(),
+                    outcome,
+                    differs: false,
+                    declared: registry
+                        .declared(day, part, version.as_deref())
+                        .filter(|_| context.is_puzzle_input())
+                        .map(String::from),
+                });
+            }
+            let main_answer = day_results
+                .iter()
+                .find(|r| r.version.is_none())
+                .map(|r| r.answer().to_owned());
+            for mut result in day_results {
+                result.differs = opts.verify_variants
+                    && main_answer
+                        .as_ref()
+                        .is_some_and(|main_answer| main_answer != result.answer());
+                let shown = opts.selects(result.version.as_deref())
+                    && !(opts.main_only && result.version.is_some());
+                if shown || result.differs {
+                    results.push(result);
                 }
-                write!(&mut results, ": ")?;
-                let sep = format!("\n{}", " ".repeat(results.chars().count() - results_start));
-                write!(&mut results, "{}", result.trim().replace('\n', &sep))?;
-                writeln!(&mut results)?;
             }
         }
     }
     Ok(results)
 }
 
+/// Replace the answers by a marker telling whether they match the expected
+/// ones, or by a masked form if the expected answer is unknown.
+fn hide_answers(
+    results: &mut [SolverResult],
+    opts: &Opts,
+    context: &InputContext,
+) -> eyre::Result<()> {
+    let expected = super::expected::read(&opts.expected_file(context)?)?;
+    for result in results {
+        let expected = super::expected::lookup(
+            &expected,
+            result.day,
+            result.part,
+            result.version.as_deref(),
+            opts.verify_variants,
+        )
+        .or(result.declared.as_deref());
+        if let Ok(outcome) = &mut result.outcome {
+            let marker = match expected {
+                Some(expected)
+                    if super::hash::matches(
+                        opts.salt.as_deref(),
+                        expected,
+                        outcome.answer.trim(),
+                    ) =>
+                {
+                    "✓"
+                }
+                Some(_) => "✗",
+                None => "[hidden]",
+            };
+            marker.clone_into(&mut outcome.answer);
+        }
+        result.declared = None;
+    }
+    Ok(())
+}
+
+/// Run a registered solver, the main version if `variant` is `None`, on the
+/// given input, normalized as the solver requests it like any other input.
+/// The input files are not read.
+pub fn solve<F>(
+    register: F,
+    day: usize,
+    part: usize,
+    variant: Option<&str>,
+    input: &str,
+) -> Result<String, Error>
+where
+    F: Fn(),
+{
+    let registry = Registry::collect(register);
+    let runner = registry
+        .runners
+        .get(&(day, part))
+        .and_then(|versions| versions.iter().find(|(v, _)| v.as_deref() == variant))
+        .map(|(_, runner)| runner)
+        .ok_or_else(|| Error::NoSolver {
+            day,
+            part,
+            variant: variant.map(String::from),
+        })?;
+    let context = InputContext::from(InputSource::Text(input.to_owned()));
+    Ok(super::input::with_input(context, || runner())?)
+}
+
+/// Run the selected solvers on an input and print their results. Return
+/// whether all of them succeeded and gave the expected answer when known.
+fn run_input(registry: &Registry, opts: &Opts, context: &InputContext) -> eyre::Result<bool> {
+    let mut results = run_solvers(registry, opts, context)?;
+    if let Some(example) = context.example()
+        && results.is_empty()
+    {
+        eyre::bail!("no example {example} found for the selected days");
+    }
+    let expected_file = opts.expected_file(context)?;
+    if opts.all_inputs || context.label().is_some() {
+        let expected = super::expected::read(&expected_file)?;
+        for result in &mut results {
+            result.declared = super::expected::lookup(
+                &expected,
+                result.day,
+                result.part,
+                result.version.as_deref(),
+                opts.verify_variants,
+            )
+            .map(String::from)
+            .or(result.declared.take());
+        }
+    }
+    if let Some(entries) = &opts.record {
+        let failures = super::record::record(
+            &results,
+            &expected_file,
+            &Recording {
+                entries,
+                force: opts.force,
+                salt: opts.salt.as_deref(),
+            },
+            opts.verify_variants,
+            opts.all
+                && opts.part.is_none()
+                && opts.variants.is_empty()
+                && opts.excluded_variants.is_empty()
+                && !opts.main_only,
+        )?;
+        for failure in failures {
+            eprintln!("{}", super::record::not_recorded(failure));
+        }
+    }
+    let success = results
+        .iter()
+        .all(|r| r.outcome.is_ok() && r.is_correct(opts.salt.as_deref()) != Some(false));
+    if opts.hide_answers {
+        hide_answers(&mut results, opts, context)?;
+    }
+    let output = super::output::render(&results, opts)?;
+    match opts.format {
+        Format::Text => {
+            if let Some(input) = context.source() {
+                println!("Input: {input}");
+            } else if let Some(example) = context.example() {
+                println!("Input: example {example}");
+            } else if let Some(name) = context.name() {
+                println!("Input: {name}");
+            } else if opts.all_inputs {
+                println!("Input: default");
+            }
+            println!("{output}");
+        }
+        _ => print!("{output}"),
+    }
+    Ok(success)
+}
+
+/// Run the solvers selected on the command line, exiting with a non-zero
+/// status if any of them fails or gives a wrong answer.
 pub fn run<F>(register: F) -> eyre::Result<()>
 where
     F: Fn(),
 {
-    color_eyre::install()?;
-    let opts = Opts::parse();
-    if opts.day.is_some() && opts.all {
-        eyre::bail!("--all and --day are not compatible");
+    let mut opts = Opts::parse();
+    if let Some(spec) = &opts.isolated_child {
+        super::isolate::child_main(register, spec, &opts);
     }
-    if opts.input.is_some() && opts.all {
+    color_eyre::install()?;
+    // The registration gives the crate name, used to locate the input files
+    let registry = Registry::collect(register);
+    let context = InputContext::from_opts(&opts)?;
+    let input = context.source();
+    if input.is_some() && opts.all {
         eyre::bail!("--all and --input are not compatible");
     }
-    if opts.part.is_some_and(|p| !(1..=2).contains(&p)) {
-        eyre::bail!("--part accepts argument must be 1 or 2");
-    }
-    if let Some(input) = opts.input {
-        super::input::OVERRIDE_INPUT.set(input).unwrap();
-    }
-    let current_day = opts.day.unwrap_or(chrono::Utc::now().day() as usize);
-    println!(
-        "{}",
-        run_tests(
-            register,
-            (!opts.all).then_some(current_day),
-            opts.part,
-            opts.main_only,
-            opts.timing
-        )?
-    );
-    Ok(())
+    if input.is_some() && opts.record.is_some() {
+        eyre::bail!("--record cannot be used with an alternate input");
+    }
+    if opts.memory && !cfg!(feature = "memory") {
+        eyre::bail!("--memory requires the `memory` feature of the `aoc` crate");
+    }
+    if opts.day.as_ref().is_some_and(|d| !d.is_within(1, 25)) {
+        eyre::bail!("--day accepts days between 1 and 25");
+    }
+    if opts.part.as_ref().is_some_and(|p| !p.is_within(1, 2)) {
+        eyre::bail!("--part accepts parts 1 and 2");
+    }
+    if opts.all {
+        opts.day = Some(Selection::all());
+    } else if opts.day.is_none() {
+        opts.day = Some(match opts.record.as_ref().and_then(RecordEntries::days) {
+            Some(days) => days.collect(),
+            None => Selection::single(chrono::Utc::now().day() as usize),
+        });
+    }
+    if opts.all_inputs && !matches!(opts.format, Format::Text) {
+        eyre::bail!("--all-inputs requires the text output format");
+    }
+    // Machine-readable formats report the errors of solvers in their entries
+    opts.keep_going |= !matches!(opts.format, Format::Text);
+    let mut contexts = Vec::new();
+    if opts.all_inputs {
+        let days = registry
+            .runners
+            .keys()
+            .map(|&(day, _)| day)
+            .filter(|&day| opts.day.as_ref().is_none_or(|d| d.contains(day)));
+        for name in context.input_names(days)? {
+            contexts.push(context.named(&name));
+        }
+    }
+    contexts.insert(0, context);
+    let mut failing = Vec::new();
+    for context in &contexts {
+        if !run_input(&registry, &opts, context)? {
+            failing.push(context.name().unwrap_or("default"));
+        }
+    }
+    if failing.is_empty() {
+        return Ok(());
+    }
+    if opts.all_inputs {
+        println!("Failing inputs: {}", failing.join(", "));
+    }
+    // The results have been reported already
+    std::io::Write::flush(&mut std::io::stdout())?;
+    std::process::exit(1);
 }
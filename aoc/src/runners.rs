@@ -1,24 +1,80 @@
 #![allow(clippy::type_complexity)]
 
 use std::{
+    cell::RefCell,
     collections::BTreeMap,
     fmt::Display,
-    sync::{LazyLock, Mutex},
+    sync::{Arc, OnceLock},
 };
 
-type Runner = dyn FnOnce() -> eyre::Result<String> + Send + Sync + 'static;
+pub(crate) type Runner = dyn Fn() -> eyre::Result<String> + Send + Sync + 'static;
 
-pub(crate) static RUNNERS: LazyLock<
-    Mutex<BTreeMap<(usize, usize), Vec<(Option<String>, Box<Runner>)>>>,
-> = LazyLock::new(|| Mutex::new(BTreeMap::new()));
+/// Solvers registered by day and part, along with the answers declared in
+/// their `#[aoc]` attribute.
+#[derive(Default)]
+pub(crate) struct Registry {
+    pub(crate) runners: BTreeMap<(usize, usize), Vec<(Option<String>, Arc<Runner>)>>,
+    pub(crate) answers: BTreeMap<(usize, usize, Option<String>), String>,
+}
+
+impl Registry {
+    /// Run the registration function and collect what it registered on the
+    /// current thread.
+    pub(crate) fn collect<F: Fn()>(register: F) -> Self {
+        register();
+        REGISTRY.with_borrow_mut(std::mem::take)
+    }
+
+    /// Answer declared for a solver, if any.
+    pub(crate) fn declared(&self, day: usize, part: usize, version: Option<&str>) -> Option<&str> {
+        self.answers
+            .get(&(day, part, version.map(String::from)))
+            .map(String::as_str)
+    }
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::default();
+}
+
+/// Name of the crate holding the solvers, shared by every thread.
+static CRATE_NAME: OnceLock<String> = OnceLock::new();
 
+/// Register the name of the crate holding the solvers, as captured when
+/// compiling it, from which the year of the puzzles may be deduced. Only the
+/// first name registered is kept.
+pub fn register_crate(name: &str) {
+    let _ = CRATE_NAME.set(name.to_owned());
+}
+
+/// Name of the crate holding the solvers, if registered.
+pub(crate) fn crate_name() -> Option<&'static str> {
+    CRATE_NAME.get().map(String::as_str)
+}
+
+/// Register a solver. Registering the same day, part and version again
+/// replaces the previous solver.
 pub fn register_runner<F, T>(day: usize, part: usize, version: Option<String>, func: F)
 where
-    F: FnOnce() -> eyre::Result<T> + Send + Sync + 'static,
+    F: Fn() -> eyre::Result<T> + Send + Sync + 'static,
     T: Display,
 {
-    let mut map = RUNNERS.lock().unwrap();
-    map.entry((day, part))
-        .or_default()
-        .push((version, Box::new(|| func().map(|r| r.to_string()))));
+    let runner: Arc<Runner> = Arc::new(move || func().map(|r| r.to_string()));
+    REGISTRY.with_borrow_mut(|registry| {
+        let versions = registry.runners.entry((day, part)).or_default();
+        match versions.iter_mut().find(|(v, _)| *v == version) {
+            Some((_, existing)) => *existing = runner,
+            None => versions.push((version, runner)),
+        }
+    });
+}
+
+/// Register the answer a solver is known to give, as declared with
+/// `answer = …` in its `#[aoc]` attribute.
+pub fn register_answer(day: usize, part: usize, version: Option<String>, answer: &str) {
+    REGISTRY.with_borrow_mut(|registry| {
+        registry
+            .answers
+            .insert((day, part, version), answer.to_owned());
+    });
 }
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+/// Selection of days or parts, written as a comma separated list of
+/// numbers and ranges such as `1-5,8,12-`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Selection(Vec<(usize, Option<usize>)>);
+
+impl Selection {
+    /// Selection containing every number starting from 1.
+    pub(crate) fn all() -> Self {
+        Selection(vec![(1, None)])
+    }
+
+    pub(crate) fn single(n: usize) -> Self {
+        Selection(vec![(n, Some(n))])
+    }
+
+    pub(crate) fn contains(&self, n: usize) -> bool {
+        self.0
+            .iter()
+            .any(|&(start, end)| start <= n && end.is_none_or(|end| n <= end))
+    }
+
+    /// Check that every selected number lies between `min` and `max`.
+    pub(crate) fn is_within(&self, min: usize, max: usize) -> bool {
+        self.0
+            .iter()
+            .all(|&(start, end)| min <= start && start <= max && end.is_none_or(|end| end <= max))
+    }
+}
+
+impl FromIterator<usize> for Selection {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        Selection(iter.into_iter().map(|n| (n, Some(n))).collect())
+    }
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("invalid number {n:?} in {s:?}"))
+        };
+        s.split(',')
+            .map(|item| match item.split_once('-') {
+                Some((start, end)) => {
+                    let start = if start.trim().is_empty() {
+                        1
+                    } else {
+                        number(start)?
+                    };
+                    let end = if end.trim().is_empty() {
+                        None
+                    } else {
+                        Some(number(end)?)
+                    };
+                    if end.is_some_and(|end| end < start) {
+                        return Err(format!("empty range {item:?} in {s:?}"));
+                    }
+                    Ok((start, end))
+                }
+                None => number(item).map(|n| (n, Some(n))),
+            })
+            .collect::<Result<_, _>>()
+            .map(Selection)
+    }
+}
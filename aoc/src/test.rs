@@ -1,51 +1,579 @@
-use eyre::{Context, bail};
-use std::path::Path;
-use std::process::Command;
+use crate::expected::Key;
+use crate::hash;
+use crate::input::InputContext;
+use crate::record::Recording;
+use crate::run::{Opts, SolverResult, execute};
+use crate::runners::Registry;
+use libtest_mimic::{Arguments, Failed, Trial};
+use owo_colors::{AnsiColors, OwoColorize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub use crate::record::RecordEntries;
+pub use crate::report::{Report, ReportFormat};
 
 const ENV_VAR: &str = "RECORD_RESULTS";
+pub(crate) const FORCE_ENV_VAR: &str = "RECORD_RESULTS_FORCE";
+const REPORT_ENV_VAR: &str = "AOC_REPORT";
+
+/// Options controlling how [`check_results_with`] checks the solvers.
+#[derive(Clone, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CheckOptions {
+    /// Skip running any alternate version.
+    pub main_only: bool,
+    /// Run every alternate version and require its answer to match the one
+    /// of the main solver. Alternate versions appear in the expected file
+    /// only when they differ from the main solver.
+    pub verify_variants: bool,
+    /// Write a `JUnit` XML or TAP report with one test case per solver. If
+    /// absent, the `AOC_REPORT` environment variable is looked up for a
+    /// report such as `junit:target/aoc.xml`.
+    pub report: Option<Report>,
+    /// Update the selected expected results instead of checking them. If
+    /// absent, the `RECORD_RESULTS` environment variable is looked up for
+    /// entries such as `1` (everything) or `day3,day5p2`.
+    pub record: Option<RecordEntries>,
+    /// Allow recording to overwrite an existing answer with a different one.
+    /// This is also the case if the `RECORD_RESULTS_FORCE` environment
+    /// variable is set.
+    pub force: bool,
+    /// Only fail on answers differing from the expected ones, and merely
+    /// warn about solvers without expected answers, expected answers without
+    /// solvers and missing input files. This is meant for work in progress,
+    /// while the default strict mode suits continuous integration.
+    pub lenient: bool,
+    /// Secret key used to record keyed hashes (HMAC-SHA256) of the answers
+    /// rather than the answers themselves, and to check hashed answers. The
+    /// key is not stored with the hashes. If absent, the `AOC_ANSWER_SALT`
+    /// environment variable is looked up.
+    pub salt: Option<String>,
+}
+
+impl CheckOptions {
+    fn report(&self) -> eyre::Result<Option<Report>> {
+        match &self.report {
+            Some(report) => Ok(Some(report.clone())),
+            None => std::env::var(REPORT_ENV_VAR)
+                .ok()
+                .map(|r| r.parse())
+                .transpose(),
+        }
+    }
+
+    fn record_entries(&self) -> eyre::Result<Option<RecordEntries>> {
+        match &self.record {
+            Some(entries) => Ok(Some(entries.clone())),
+            None => std::env::var(ENV_VAR)
+                .ok()
+                .map(|entries| entries.parse().map_err(|e| eyre::eyre!("{ENV_VAR}: {e}")))
+                .transpose(),
+        }
+    }
+}
+
+fn title(day: usize, part: usize, version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("Day {day} part {part} — {version}"),
+        None => format!("Day {day} part {part}"),
+    }
+}
+
+fn colored(s: &str, color: owo_colors::AnsiColors) -> String {
+    if std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+        s.color(color).to_string()
+    } else {
+        s.to_owned()
+    }
+}
 
-fn equal_content<P: AsRef<Path>>(actual: &str, expected: P, show_diff: bool) -> eyre::Result<bool> {
-    let expected_content = std::fs::read_to_string(expected.as_ref()).context(format!(
-        "cannot read {}",
-        expected.as_ref().to_string_lossy()
-    ))?;
-    if actual == expected_content {
-        Ok(true)
+/// Describe an answer differing from the expected one, on a single line if
+/// both are single-line answers.
+fn mismatch(expected: &str, actual: &str) -> String {
+    let (expected, actual) = (
+        colored(expected, AnsiColors::Green),
+        colored(actual, AnsiColors::Red),
+    );
+    if expected.contains('\n') || actual.contains('\n') {
+        format!("expected:\n{expected}\ngot:\n{actual}")
     } else {
-        if show_diff {
-            let temp = mktemp::Temp::new_file()?;
-            std::fs::write(&temp, actual)?;
-            let diff = Command::new("diff")
-                .arg("-u")
-                .arg(expected.as_ref())
-                .arg(temp.as_path())
-                .output()?;
-            if !diff.stderr.is_empty() {
-                bail!(String::from_utf8(diff.stderr)?);
+        format!("expected {expected}, got {actual}")
+    }
+}
+
+/// Problem found when checking the results against the expected answers.
+enum Diagnostic<'a> {
+    /// The answer differs from the expected one
+    Mismatch {
+        result: &'a SolverResult,
+        expected: &'a str,
+    },
+    /// A registered solver has no expected answer
+    Unexpected { result: &'a SolverResult },
+    /// An expected answer has no registered solver, for example because it
+    /// has been renamed or deleted
+    Stale { key: &'a Key, expected: &'a str },
+    /// The input file is missing for a day whose solvers failed
+    MissingInput { day: usize },
+}
+
+impl Diagnostic<'_> {
+    /// Check whether the diagnostic makes the check fail. In lenient mode,
+    /// only differing answers do.
+    fn fails(&self, lenient: bool) -> bool {
+        !lenient || matches!(self, Diagnostic::Mismatch { .. })
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Diagnostic::Mismatch { result, expected } => format!(
+                "{}: {}",
+                title(result.day, result.part, result.version.as_deref()),
+                mismatch(expected, result.answer())
+            ),
+            Diagnostic::Unexpected { result } => format!(
+                "{}: no expected answer, got {}",
+                title(result.day, result.part, result.version.as_deref()),
+                colored(result.answer(), AnsiColors::Red)
+            ),
+            Diagnostic::Stale {
+                key: (day, part, version),
+                expected,
+            } => format!(
+                "{}: expected {}, but no solver is registered",
+                title(*day, *part, version.as_deref()),
+                colored(expected, AnsiColors::Green)
+            ),
+            Diagnostic::MissingInput { day } => {
+                match super::input::current(|context| context.input_file(*day)) {
+                    Ok(path) => format!("Day {day}: input file {} is missing", path.display()),
+                    Err(e) => format!("Day {day}: {e}"),
+                }
             }
-            println!(
-                "Actual does not meet expected:\n{}",
-                String::from_utf8(diff.stdout)?
-            );
-            println!("\nRe-run with {ENV_VAR}=1 to update reference files");
         }
-        Ok(false)
     }
 }
 
+/// Days of the failing results whose input file is missing.
+fn missing_inputs(results: &[SolverResult], context: &InputContext) -> BTreeSet<usize> {
+    results
+        .iter()
+        .filter(|r| r.outcome.is_err() && context.is_missing(r.day))
+        .map(|r| r.day)
+        .collect()
+}
+
+/// Prefix of the diagnostics of an additional input.
+fn prefix(context: &InputContext) -> String {
+    context
+        .name()
+        .map(|name| format!("[{name}] "))
+        .unwrap_or_default()
+}
+
+/// Compare every result with its expected answer, printing a report of the
+/// differences. `registered` holds the solvers which have been registered,
+/// whether they have been run or not. Diagnostics are prefixed with the
+/// name of the additional input being checked, if any.
+fn compare(
+    results: &[SolverResult],
+    expected: &BTreeMap<Key, String>,
+    registered: &BTreeSet<Key>,
+    context: &InputContext,
+    options: &CheckOptions,
+) -> bool {
+    let prefix = prefix(context);
+    let key = hash::key(options.salt.as_deref());
+    let missing_inputs = missing_inputs(results, context);
+    let mut diagnostics = Vec::new();
+    for result in results {
+        if result.outcome.is_err() && missing_inputs.contains(&result.day) {
+            continue;
+        }
+        match super::expected::lookup(
+            expected,
+            result.day,
+            result.part,
+            result.version.as_deref(),
+            options.verify_variants,
+        )
+        .or(result.declared.as_deref())
+        {
+            Some(expected) if hash::matches(key.as_deref(), expected, result.answer()) => (),
+            Some(expected) => diagnostics.push(Diagnostic::Mismatch { result, expected }),
+            None => diagnostics.push(Diagnostic::Unexpected { result }),
+        }
+    }
+    for (key, expected) in expected {
+        if !registered.contains(key) {
+            diagnostics.push(Diagnostic::Stale { key, expected });
+        }
+    }
+    for day in missing_inputs {
+        diagnostics.push(Diagnostic::MissingInput { day });
+    }
+    for diagnostic in &diagnostics {
+        if diagnostic.fails(options.lenient) {
+            println!("{prefix}{}", diagnostic.message());
+        } else {
+            println!("warning: {prefix}{}", diagnostic.message());
+        }
+    }
+    let success = !diagnostics.iter().any(|d| d.fails(options.lenient));
+    if diagnostics.iter().any(|d| {
+        d.fails(options.lenient)
+            && matches!(
+                d,
+                Diagnostic::Mismatch { .. } | Diagnostic::Unexpected { .. }
+            )
+    }) {
+        println!("\nRe-run with {ENV_VAR}=1 to update reference files");
+    }
+    success
+}
+
+/// Check that a solver gives the answer declared with `answer = …` in its
+/// `#[aoc]` attribute. This is used by the tests generated for such solvers.
+/// Nothing is checked when the inputs of a user profile are selected, as the
+/// declared answer only holds for the default inputs.
+pub fn assert_answer<T: std::fmt::Display>(
+    expected: &str,
+    runner: impl FnOnce() -> eyre::Result<T>,
+) {
+    if let Ok(Some(_)) = super::input::current(InputContext::user) {
+        return;
+    }
+    let answer = match runner() {
+        Ok(answer) => answer.to_string(),
+        Err(e) => panic!("no answer: {e:?}"),
+    };
+    let answer = answer.trim();
+    assert!(
+        hash::matches(hash::key(None).as_deref(), expected, answer),
+        "{}",
+        mismatch(expected, answer)
+    );
+}
+
 pub fn check_results<F: Fn(), P: AsRef<Path>>(
     register: F,
     expected: P,
     main_only: bool,
 ) -> eyre::Result<bool> {
-    let actual = super::run::run_tests(register, None, None, false, main_only)?;
-    let update = std::env::var(ENV_VAR).is_ok();
-    if update {
-        if !matches!(equal_content(&actual, &expected, false), Ok(true)) {
-            std::fs::write(expected, actual)?;
+    check_results_with(
+        register,
+        expected,
+        &CheckOptions {
+            main_only,
+            ..Default::default()
+        },
+    )
+}
+
+/// Expected results file of the user whose inputs are used, if any.
+fn user_expected(context: &InputContext, expected: &Path) -> eyre::Result<PathBuf> {
+    Ok(match context.user()? {
+        Some(user) => super::expected::for_user(expected, &user),
+        None => expected.to_owned(),
+    })
+}
+
+/// Check the solvers against the expected results, then against the ones of
+/// every additional input such as `input/day5/alice.txt`, found in files such
+/// as `expected-alice.txt` next to `expected`.
+pub fn check_results_with<F: Fn(), P: AsRef<Path>>(
+    register: F,
+    expected: P,
+    options: &CheckOptions,
+) -> eyre::Result<bool> {
+    let registry = Registry::collect(register);
+    let context = super::input::current(InputContext::clone);
+    let expected = &user_expected(&context, expected.as_ref())?;
+    let names = context.input_names(registry.runners.keys().map(|&(day, _)| day))?;
+    let mut success = check_input(&registry, &context, expected, options)?;
+    for name in names {
+        let expected = super::expected::for_input(expected, &name);
+        success &= check_input(&registry, &context.named(&name), &expected, options)?;
+    }
+    Ok(success)
+}
+
+fn check_input(
+    registry: &Registry,
+    context: &InputContext,
+    expected: &Path,
+    options: &CheckOptions,
+) -> eyre::Result<bool> {
+    let opts = super::run::Opts {
+        main_only: options.main_only || options.verify_variants,
+        verify_variants: options.verify_variants,
+        keep_going: true,
+        ..Default::default()
+    };
+    let results = super::run::run_solvers(registry, &opts, context)?;
+    let registered = registry
+        .runners
+        .iter()
+        .flat_map(|(&(day, part), versions)| {
+            versions
+                .iter()
+                .map(move |(version, _)| (day, part, version.clone()))
+        })
+        .collect::<BTreeSet<_>>();
+    // The report only covers the main input
+    if let Some(report) = options.report()?
+        && context.name().is_none()
+    {
+        let expected = super::expected::read(expected)?;
+        super::report::write(&report, &results, &expected, options)?;
+    }
+    if let Some(entries) = options.record_entries()? {
+        let salt = hash::key(options.salt.as_deref());
+        let failures = super::record::record(
+            &results,
+            expected,
+            &Recording {
+                entries: &entries,
+                force: options.force || std::env::var(FORCE_ENV_VAR).is_ok(),
+                salt: salt.as_deref(),
+            },
+            options.verify_variants,
+            !options.main_only,
+        )?;
+        for failure in &failures {
+            println!(
+                "{}{}",
+                prefix(context),
+                super::record::not_recorded(failure)
+            );
         }
-        Ok(true)
+        Ok(failures.is_empty())
     } else {
-        equal_content(&actual, &expected, !update)
+        let expected = super::expected::read(expected)?;
+        Ok(compare(&results, &expected, &registered, context, options))
     }
 }
+
+/// Results of the solvers run by the test harness, used to write a report.
+type Executed = Arc<Mutex<Vec<SolverResult>>>;
+
+fn trials<F: Fn()>(
+    register: F,
+    expected: &Path,
+    options: &CheckOptions,
+    executed: &Executed,
+) -> eyre::Result<Vec<Trial>> {
+    if options.record_entries()?.is_some() {
+        check_results_with(&register, expected, options)?;
+    }
+    let context = super::input::current(InputContext::clone);
+    let expected = user_expected(&context, expected)?;
+    let registry = Registry::collect(register);
+    let names = context.input_names(registry.runners.keys().map(|&(day, _)| day))?;
+    let mut trials = input_trials(&registry, &context, &expected, options, Some(executed))?;
+    for name in names {
+        trials.extend(input_trials(
+            &registry,
+            &context.named(&name),
+            &super::expected::for_input(&expected, &name),
+            options,
+            None,
+        )?);
+    }
+    Ok(trials)
+}
+
+/// Name of the trial of a solver on an input.
+fn trial_name(context: &InputContext, day: usize, part: usize, version: Option<&str>) -> String {
+    let name = format!("day{day}::part{part}::{}", version.unwrap_or("main"));
+    match context.name() {
+        Some(input) => format!("{input}::{name}"),
+        None => name,
+    }
+}
+
+/// Build the failing trials of the expected answers without solver.
+fn stale_trials(
+    registry: &Registry,
+    context: &InputContext,
+    expected: &BTreeMap<Key, String>,
+    options: &CheckOptions,
+) -> Vec<Trial> {
+    let mut stale = Vec::new();
+    for (key, answer) in expected {
+        let (day, part, version) = key;
+        if registry
+            .runners
+            .get(&(*day, *part))
+            .is_some_and(|versions| versions.iter().any(|(v, _)| v == version))
+        {
+            continue;
+        }
+        let message = Diagnostic::Stale {
+            key,
+            expected: answer,
+        }
+        .message();
+        stale.push(
+            Trial::test(
+                trial_name(context, *day, *part, version.as_deref()),
+                move || Err(message.into()),
+            )
+            .with_ignored_flag(options.lenient),
+        );
+    }
+    stale
+}
+
+/// Build the trials checking the solvers on one input. Those of an
+/// additional input are prefixed by its name, such as
+/// `alice::day1::part1::main`, and only cover the days having this input.
+/// The results of the solvers are added to `executed` if present.
+fn input_trials(
+    registry: &Registry,
+    context: &InputContext,
+    expected: &Path,
+    options: &CheckOptions,
+    executed: Option<&Executed>,
+) -> eyre::Result<Vec<Trial>> {
+    let expected = super::expected::read(expected)?;
+    let stale = stale_trials(registry, context, &expected, options);
+    let mut trials = Vec::new();
+    for (&(day, part), versions) in &registry.runners {
+        if context.skips(day) {
+            continue;
+        }
+        let missing_input = context.is_missing(day);
+        for (version, runner) in versions {
+            if options.main_only && !options.verify_variants && version.is_some() {
+                continue;
+            }
+            let name = trial_name(context, day, part, version.as_deref());
+            // Declared answers only hold for the inputs of the default user
+            let declared = registry
+                .declared(day, part, version.as_deref())
+                .filter(|_| context.is_puzzle_input())
+                .map(String::from);
+            let expected = super::expected::lookup(
+                &expected,
+                day,
+                part,
+                version.as_deref(),
+                options.verify_variants,
+            )
+            .map(String::from)
+            .or_else(|| declared.clone());
+            let ignored = options.lenient && (missing_input || expected.is_none());
+            let (context, runner, version) = (context.clone(), Arc::clone(runner), version.clone());
+            let executed = executed.map(Arc::clone);
+            let key = hash::key(options.salt.as_deref());
+            trials.push(
+                Trial::test(name, move || {
+                    let (outcome, failure) = super::input::with_input(context, || {
+                        let outcome = execute(runner.as_ref(), &Opts::default());
+                        let failure = outcome.as_ref().err().map(|e| {
+                            if missing_input {
+                                Failed::from(Diagnostic::MissingInput { day }.message())
+                            } else {
+                                Failed::from(format!("{e:?}"))
+                            }
+                        });
+                        (outcome, failure)
+                    });
+                    let result = SolverResult {
+                        day,
+                        part,
+                        version,
+                        outcome: outcome.map_err(|e| format!("error: {e:#}")),
+                        differs: false,
+                        declared,
+                    };
+                    let answer = result.answer().to_owned();
+                    if let Some(executed) = executed {
+                        executed.lock().unwrap().push(result);
+                    }
+                    if let Some(failure) = failure {
+                        return Err(failure);
+                    }
+                    let answer = answer.as_str();
+                    let failure = match expected {
+                        Some(expected) if hash::matches(key.as_deref(), &expected, answer) => {
+                            return Ok(());
+                        }
+                        Some(expected) => mismatch(&expected, answer),
+                        None => format!(
+                            "no expected answer, got {}",
+                            colored(answer, AnsiColors::Red)
+                        ),
+                    };
+                    Err(
+                        format!("{failure}\n\nRe-run with {ENV_VAR}=1 to update reference files")
+                            .into(),
+                    )
+                })
+                .with_ignored_flag(ignored),
+            );
+        }
+    }
+    trials.extend(stale);
+    Ok(trials)
+}
+
+/// Run every registered solver as an individual test named after its day,
+/// part and version, such as `day3::part1::main`, and check its answer
+/// against the expected results. This is meant to be called from the `main`
+/// function of a test target declared with `harness = false`, for example
+/// through [`expected_tests!`](crate::expected_tests).
+///
+/// If the `RECORD_RESULTS` environment variable is set, the selected expected
+/// results are updated first, as with [`check_results_with`]. A report
+/// requested through `options` or `AOC_REPORT` gets one test case per solver
+/// which has been run.
+pub fn harness<F: Fn(), P: AsRef<Path>>(register: F, expected: P, options: &CheckOptions) -> ! {
+    let args = Arguments::from_args();
+    let executed = Executed::default();
+    let trials = trials(register, expected.as_ref(), options, &executed).unwrap_or_else(|e| {
+        let message = format!("{e:?}");
+        vec![Trial::test("check_expected", move || Err(message.into()))]
+    });
+    let conclusion = libtest_mimic::run(&args, trials);
+    if let Err(e) = write_report(expected.as_ref(), options, &executed) {
+        eprintln!("cannot write the report: {e:?}");
+        std::process::exit(101);
+    }
+    conclusion.exit()
+}
+
+/// Write the report of the solvers run by the test harness, if any is
+/// requested.
+fn write_report(expected: &Path, options: &CheckOptions, executed: &Executed) -> eyre::Result<()> {
+    let Some(report) = options.report()? else {
+        return Ok(());
+    };
+    let context = super::input::current(InputContext::clone);
+    let expected = super::expected::read(&user_expected(&context, expected)?)?;
+    let mut results = std::mem::take(&mut *executed.lock().unwrap());
+    results.sort_by(|a, b| (a.day, a.part, &a.version).cmp(&(b.day, b.part, &b.version)));
+    super::report::write(&report, &results, &expected, options)
+}
+
+/// Define the `main` function of a test target declared with
+/// `harness = false` in `Cargo.toml`, running every solver as an
+/// individual test. Options may be given as an optional third argument.
+///
+/// ```ignore
+/// aoc::expected_tests!(aoc2023::register::register_runners, "expected.txt");
+/// ```
+#[macro_export]
+macro_rules! expected_tests {
+    ($register:expr, $expected:expr) => {
+        $crate::expected_tests!($register, $expected, $crate::test::CheckOptions::default());
+    };
+    ($register:expr, $expected:expr, $options:expr) => {
+        fn main() {
+            $crate::test::harness($register, $expected, &$options)
+        }
+    };
+}
@@ -0,0 +1,266 @@
+use aoc::input::{InputContext, with_input};
+use aoc::test::{CheckOptions, RecordEntries, check_results_with};
+use aoc_derive::aoc;
+use std::path::PathBuf;
+
+#[aoc(day1, part1)]
+fn day1_part1() -> usize {
+    10
+}
+
+#[aoc(day1, part1, other)]
+fn day1_part1_other() -> usize {
+    20
+}
+
+#[aoc(day2, part1)]
+fn day2_part1() -> String {
+    String::from("a\nb\n")
+}
+
+#[aoc(day3, part1)]
+fn day3_part1(input: &str) -> usize {
+    input.len()
+}
+
+fn register() {
+    aoc::register_runner(1, 1, None, runner_1_1_none);
+    aoc::register_runner(1, 1, Some(String::from("other")), runner_1_1_other);
+    aoc::register_runner(2, 1, None, runner_2_1_none);
+}
+
+#[test]
+fn check() {
+    assert!(!check_results_with(register, "tests/answers.toml", &CheckOptions::default()).unwrap());
+}
+
+#[test]
+fn record() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers.toml");
+    std::fs::copy("tests/answers.toml", &path).unwrap();
+    let mut options = CheckOptions {
+        record: Some(RecordEntries::all()),
+        ..Default::default()
+    };
+    let err = check_results_with(register, &path, &options).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("day 1 part 1 (other): 10 would become 20")
+    );
+    options.force = true;
+    assert!(check_results_with(register, &path, &options).unwrap());
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    # Answers of the test solvers
+
+    [day1.part1]
+    main = 10 # checked by hand
+    other = 20 # to be checked
+
+    [day2.part1]
+    main = '''
+    a
+    b'''
+    "###);
+    assert!(check_results_with(register, &path, &CheckOptions::default()).unwrap());
+}
+
+#[test]
+fn record_new() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-new.toml");
+    let _ = std::fs::remove_file(&path);
+    let options = CheckOptions {
+        record: Some(RecordEntries::all()),
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &options).unwrap());
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    [day1.part1]
+    main = 10
+    other = 20
+
+    [day2.part1]
+    main = '''
+    a
+    b'''
+    "###);
+}
+
+#[test]
+fn no_answer_produced() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-stale.toml");
+    std::fs::write(
+        &path,
+        "[day1.part1]\nmain = 10\nother = 20\n\n[day2.part1]\nmain = \"a\\nb\"\n\n[day3.part1]\nmain = 30\n",
+    )
+    .unwrap();
+    assert!(!check_results_with(register, &path, &CheckOptions::default()).unwrap());
+    let lenient = CheckOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &lenient).unwrap());
+    std::fs::write(
+        &path,
+        "[day1.part1]\nmain = 10\nother = 20\n\n[day2.part1]\nmain = \"a\\nb\"\n",
+    )
+    .unwrap();
+    assert!(check_results_with(register, &path, &CheckOptions::default()).unwrap());
+}
+
+#[test]
+fn record_selected() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-selected.txt");
+    std::fs::write(&path, "Day 1 - part 1: 10\nDay 1 - part 1 — other: 10\n").unwrap();
+    let options = CheckOptions {
+        record: Some("day2p1".parse().unwrap()),
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &options).unwrap());
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    Day 1 - part 1: 10
+    Day 1 - part 1 — other: 10
+    Day 2 - part 1: a
+                    b
+    "###);
+}
+
+#[test]
+fn missing_input() {
+    let register = || {
+        register();
+        aoc::register_runner(3, 1, None, runner_3_1_none);
+    };
+    let lenient = CheckOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    assert!(!check_results_with(register, "tests/answers.toml", &lenient).unwrap());
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-input.toml");
+    std::fs::write(
+        &path,
+        "[day1.part1]\nmain = 10\nother = 20\n\n[day2.part1]\nmain = \"a\\nb\"\n",
+    )
+    .unwrap();
+    assert!(check_results_with(register, &path, &lenient).unwrap());
+    assert!(!check_results_with(register, &path, &CheckOptions::default()).unwrap());
+}
+
+#[test]
+fn record_hashed() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-hashed.toml");
+    std::fs::write(&path, "[day1.part1]\nmain = 10\n").unwrap();
+    let options = CheckOptions {
+        record: Some(RecordEntries::all()),
+        salt: Some(String::from("pepper")),
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &options).unwrap());
+    // The key is not stored with the hashes
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    [day1.part1]
+    main = "hmac-sha256:74543464e55c2a7fc2fa568cc8b70abdeb838b664a45f5d26e703c0a570e39de"
+    other = "hmac-sha256:cc8890497ed92b768d25450a242c0e325bbeb7140a1820c9e712b63a6fad5efc"
+
+    [day2.part1]
+    main = "hmac-sha256:dbb240814e9864243d3773dd460aa12dd54e980ab9b49d1f54522be4537ad66b"
+    "###);
+    let with_key = |key: &str| CheckOptions {
+        salt: Some(String::from(key)),
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &with_key("pepper")).unwrap());
+    assert!(!check_results_with(register, &path, &with_key("salt")).unwrap());
+}
+
+#[test]
+fn additional_inputs() {
+    let register = || {
+        register();
+        aoc::register_runner(3, 1, None, runner_3_1_none);
+    };
+    let lenient = CheckOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("additional-inputs");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("answers.toml");
+    std::fs::write(
+        &path,
+        "[day1.part1]\nmain = 10\nother = 20\n\n[day2.part1]\nmain = \"a\\nb\"\n",
+    )
+    .unwrap();
+    // tests/input/day3/alice.txt holds 6 bytes
+    let check = || {
+        let context = InputContext::from_dir("tests/input").unwrap();
+        with_input(context, || {
+            check_results_with(register, &path, &lenient).unwrap()
+        })
+    };
+    std::fs::write(dir.join("answers-alice.toml"), "[day3.part1]\nmain = 5\n").unwrap();
+    assert!(!check());
+    std::fs::write(dir.join("answers-alice.toml"), "[day3.part1]\nmain = 6\n").unwrap();
+    assert!(check());
+}
+
+#[test]
+fn years() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-years.toml");
+    std::fs::write(
+        &path,
+        "[day1.part1]\nmain = 30\n\n[year2015.day1.part1]\nmain = 10\nother = 20\n",
+    )
+    .unwrap();
+    let options = CheckOptions {
+        record: Some(RecordEntries::all()),
+        ..Default::default()
+    };
+    assert!(check_results_with(register, &path, &options).unwrap());
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    [day1.part1]
+    main = 30
+
+    [year2015.day1.part1]
+    main = 10
+    other = 20
+
+    [year2015.day2.part1]
+    main = '''
+    a
+    b'''
+    "###);
+    assert!(check_results_with(register, &path, &CheckOptions::default()).unwrap());
+    std::fs::write(
+        &path,
+        "[year2015.day1.part1]\nmain = 10\n\n[year2016.day1.part1]\nmain = 10\n",
+    )
+    .unwrap();
+    let err = check_results_with(register, &path, &CheckOptions::default()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("cannot determine the year of the answers, set AOC_YEAR")
+    );
+}
+
+#[test]
+fn record_failure() {
+    let register = || {
+        register();
+        aoc::register_runner(3, 1, None, runner_3_1_none);
+    };
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-failure.txt");
+    std::fs::write(&path, "Day 1 - part 1: 10\nDay 3 - part 1: 7\n").unwrap();
+    let options = CheckOptions {
+        record: Some(RecordEntries::all()),
+        ..Default::default()
+    };
+    // The input of day 3 is missing
+    assert!(!check_results_with(register, &path, &options).unwrap());
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r###"
+    Day 1 - part 1: 10
+    Day 1 - part 1 — other: 20
+    Day 2 - part 1: a
+                    b
+    Day 3 - part 1: 7
+    "###);
+}
@@ -102,27 +102,219 @@ impl FromStr for S {
     }
 }
 
+fn with_input_file<T>(path: &str, f: impl FnOnce() -> T) -> T {
+    let source = aoc::input::InputSource::from_file(path).unwrap();
+    aoc::input::with_input(source.into(), f)
+}
+
 #[test]
 fn inputs() {
-    aoc::input::OVERRIDE_INPUT
-        .set(String::from("tests/input.txt"))
-        .unwrap();
-    assert_eq!("123102030", runner_3_1_str().unwrap());
+    with_input_file("tests/input.txt", || {
+        assert_eq!("123102030", runner_3_1_str().unwrap());
+        assert_eq!(
+            r#"["1", "10", "2", "20", "3", "30"]"#,
+            runner_3_1_vec_str().unwrap()
+        );
+        assert_eq!(
+            r#"["1", "2", "3", "10", "20", "30"]"#,
+            runner_3_1_ref_str().unwrap()
+        );
+        assert_eq!(
+            r#"["1", "10", "2", "20", "3", "30"]"#,
+            runner_3_1_mut_str().unwrap()
+        );
+        assert_eq!("123102030", runner_3_1_bytes().unwrap());
+        assert_eq!(66, runner_3_1_u32().unwrap());
+        assert_eq!(12, runner_3_1_s().unwrap());
+        assert_eq!(12, runner_3_1_s_vec().unwrap());
+        assert_eq!(12, runner_3_1_s_mut().unwrap());
+    });
+}
+
+#[test]
+fn inputs_commas() {
+    with_input_file("tests/input-commas.txt", || {
+        assert_eq!(66, runner_3_1_u32_commas().unwrap());
+        assert_eq!(12, runner_3_1_s_commas().unwrap());
+    });
+}
+
+#[test]
+fn concurrent_inputs() {
+    std::thread::scope(|scope| {
+        let commas =
+            scope.spawn(|| with_input_file("tests/input-commas.txt", runner_3_1_u32_commas));
+        let lines = scope.spawn(|| with_input_file("tests/input.txt", runner_3_1_u32));
+        assert_eq!(66, commas.join().unwrap().unwrap());
+        assert_eq!(66, lines.join().unwrap().unwrap());
+    });
+}
+
+#[aoc(day4, part1, answer = 42)]
+fn day4_part1() -> usize {
+    42
+}
+
+#[aoc(day4, part2, answer = "A,B")]
+fn day4_part2() -> &'static str {
+    "A,B"
+}
+
+#[aoc(day4, part2, negative, answer = -3)]
+fn day4_part2_negative() -> i32 {
+    -3
+}
+
+#[test]
+fn answer() {
+    assert_eq!("42", answer_4_1_none());
+    assert_eq!("A,B", answer_4_2_none());
+    assert_eq!("-3", answer_4_2_negative());
+}
+
+#[aoc(
+    day5,
+    part1,
+    param(width = 101, height = 103),
+    example_param(width = 11, height = 7)
+)]
+fn day5_part1(input: &str, width: usize, height: usize) -> usize {
+    input.trim().len() * width * height
+}
+
+#[aoc(day5, part2, param(steps = 1000), example_param(steps = 10))]
+fn day5_part2(steps: u32) -> u32 {
+    steps
+}
+
+#[test]
+fn params() {
+    let input =
+        || aoc::input::InputContext::from(aoc::input::InputSource::Text(String::from("ab")));
+    aoc::input::with_input(input(), || {
+        assert_eq!(2 * 101 * 103, runner_5_1_none().unwrap());
+        assert_eq!(1000, runner_5_2_none().unwrap());
+    });
+    aoc::input::with_input(input().as_example(1), || {
+        assert_eq!(2 * 11 * 7, runner_5_1_none().unwrap());
+        assert_eq!(10, runner_5_2_none().unwrap());
+    });
+}
+
+#[aoc(day6, part1)]
+fn day6_part1(input: &str) -> String {
+    format!("{input:?}")
+}
+
+#[aoc(day6, part1, raw, normalize = raw)]
+fn day6_part1_raw(input: &str) -> String {
+    format!("{input:?}")
+}
+
+#[aoc(day6, part1, trim, normalize = trim)]
+fn day6_part1_trim(input: &str) -> String {
+    format!("{input:?}")
+}
+
+#[aoc(day6, part2, separator = ',')]
+fn day6_part2(input: &[u32]) -> u32 {
+    input.iter().sum()
+}
+
+#[test]
+fn normalization() {
+    let input = "\u{feff} 1,2\r\n3\r\n\n\n";
+    let normalized = |variant| {
+        aoc::input::with_input(
+            aoc::input::InputSource::Text(String::from(input)).into(),
+            || match variant {
+                "raw" => runner_6_1_raw(),
+                "trim" => runner_6_1_trim(),
+                _ => runner_6_1_none(),
+            },
+        )
+        .unwrap()
+    };
+    assert_eq!(r#"" 1,2\n3\n""#, normalized("lines"));
+    assert_eq!(r#""\u{feff} 1,2\r\n3\r\n\n\n""#, normalized("raw"));
+    assert_eq!(r#""1,2\n3""#, normalized("trim"));
+    assert_eq!("6", aoc::solve(register, 6, 2, None, "1,2,3\r\n").unwrap());
+}
+
+#[test]
+fn parse_input_bytes() {
+    use aoc::input::parse_input_bytes;
     assert_eq!(
-        r#"["1", "10", "2", "20", "3", "30"]"#,
-        runner_3_1_vec_str().unwrap()
+        vec![b"1", b"2"],
+        parse_input_bytes(b"1\n2\n", None).unwrap()
     );
     assert_eq!(
-        r#"["1", "2", "3", "10", "20", "30"]"#,
-        runner_3_1_ref_str().unwrap()
+        vec![b"1", b"2"],
+        parse_input_bytes(b"1,2\n", Some(b',')).unwrap()
+    );
+}
+
+#[test]
+fn parse_error() {
+    let err = aoc::input::parse_day_input::<u32>(7, "1,2,3,x5,6\n", Some(",")).unwrap_err();
+    assert!(matches!(
+        err,
+        aoc::error::Error::Parse {
+            day: Some(7),
+            line: 1,
+            column: 7,
+            ..
+        }
+    ));
+    let err = aoc::input::parse_day_input::<u32>(7, "1\n2\n3x\n", None).unwrap_err();
+    insta::assert_snapshot!(format!("{:#}", eyre::Report::new(err)), @r"
+    cannot parse line 3, column 1 of the input of day 7
+      |
+    3 | 3x
+      | ^: invalid digit found in string
+    ");
+    let err = aoc::input::parse_input::<u32>("1\n2x\n", None).unwrap_err();
+    insta::assert_snapshot!(err.to_string(), @r"
+    cannot parse line 2, column 1 of the input
+      |
+    2 | 2x
+      | ^
+    ");
+    let err = aoc::error::Error::Parse {
+        day: None,
+        line: 1,
+        column: 0,
+        content: String::from("x"),
+        source: "invalid".into(),
+    };
+    insta::assert_snapshot!(err.to_string(), @r"
+    cannot parse line 1, column 0 of the input
+      |
+    1 | x
+      | ^
+    ");
+}
+
+fn register() {
+    aoc::register_runner(1, 1, None, runner_1_1_none);
+    aoc::register_runner(3, 1, Some(String::from("u32")), runner_3_1_u32);
+    aoc::register_runner(3, 1, Some(String::from("str")), runner_3_1_str);
+    aoc::register_runner(6, 2, None, runner_6_2_none);
+}
+
+#[test]
+fn solve() {
+    assert_eq!("30", aoc::solve(register, 1, 1, None, "").unwrap());
+    assert_eq!(
+        "6",
+        aoc::solve(register, 3, 1, Some("u32"), "1\n2\n3\n").unwrap()
     );
     assert_eq!(
-        r#"["1", "10", "2", "20", "3", "30"]"#,
-        runner_3_1_mut_str().unwrap()
+        "12",
+        aoc::solve(register, 3, 1, Some("str"), "1\n2").unwrap()
     );
-    assert_eq!("123102030", runner_3_1_bytes().unwrap());
-    assert_eq!(66, runner_3_1_u32().unwrap());
-    assert_eq!(12, runner_3_1_s().unwrap());
-    assert_eq!(12, runner_3_1_s_vec().unwrap());
-    assert_eq!(12, runner_3_1_s_mut().unwrap());
+    let err = aoc::solve(register, 3, 1, None, "1").unwrap_err();
+    assert_eq!("no solver registered for day 3 part 1", err.to_string());
+    let err = aoc::solve(register, 3, 1, Some("u32"), "x").unwrap_err();
+    assert!(matches!(err, aoc::error::Error::Other(_)));
 }
@@ -0,0 +1,26 @@
+use aoc::test::{CheckOptions, check_results_with};
+use aoc_derive::aoc;
+use std::path::PathBuf;
+
+#[aoc(day1, part1)]
+fn day1_part1() -> usize {
+    10
+}
+
+fn register() {
+    aoc::register_crate("aoc2016");
+    aoc::register_runner(1, 1, None, runner_1_1_none);
+}
+
+#[test]
+fn registered_crate() {
+    // The year is found in the registered crate name rather than in the
+    // name of the running crate
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("answers-crate-year.toml");
+    std::fs::write(
+        &path,
+        "[year2015.day1.part1]\nmain = 20\n\n[year2016.day1.part1]\nmain = 10\n",
+    )
+    .unwrap();
+    assert!(check_results_with(register, &path, &CheckOptions::default()).unwrap());
+}
@@ -2,8 +2,14 @@ use serial_test::serial;
 use std::process::Command;
 
 fn run_with(args: &[&str]) -> String {
+    run_with_features(&[], args)
+}
+
+fn run_with_features(features: &[&str], args: &[&str]) -> String {
     let output = Command::new("cargo")
-        .args(["run", "--"])
+        .arg("run")
+        .args(features.iter().flat_map(|f| ["--features", f]))
+        .arg("--")
         .args(args)
         .current_dir("../dummy-year")
         .output()
@@ -32,6 +38,310 @@ fn day1_main() {
     "###);
 }
 
+#[test]
+#[serial]
+fn day1_isolated() {
+    insta::assert_snapshot!(run_with(&["-d", "1", "--isolate", "--max-memory", "1G"]), @r###"
+    Day 1 - part 1: 232
+    Day 1 - part 1 — str_slice: 232
+    Day 1 - part 2: 1783
+    Day 1 - part 2 — result: 1783
+    Day 1 - part 2 — result_string: 1783
+    "###);
+}
+
+#[test]
+#[serial]
+fn isolated_solver_output() {
+    insta::assert_snapshot!(run_with_features(&["chatty"], &["-d", "3", "--isolate"]), @r###"
+    1000 lines
+    Day 3 - part 1: 8134 ✓
+
+    "###);
+}
+
+#[test]
+#[serial]
+fn isolated_solver_panic() {
+    let output = run_with_features(
+        &["faulty"],
+        &[
+            "-d",
+            "3",
+            "--isolate",
+            "--input-string",
+            "boom",
+            "--format",
+            "json",
+        ],
+    );
+    let results: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let error = results[0]["error"].as_str().unwrap();
+    assert!(error.contains("panicked at"), "{error}");
+    assert!(error.contains("boom in solver"), "{error}");
+}
+
+#[test]
+#[serial]
+fn input_string() {
+    insta::assert_snapshot!(run_with(&["-d", "1", "-p", "1", "-m", "--input-string", "(()"]), @r###"
+    Input: string given on the command line
+    Day 1 - part 1: 1
+    "###);
+}
+
+#[test]
+#[serial]
+fn input_file_missing() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "-d", "1", "--input", "input/nonexistent.txt"])
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("cannot read input file input/nonexistent.txt")
+    );
+}
+
+#[test]
+#[serial]
+fn input_dir_template() {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("inputs");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy("../dummy-year/input/day3.txt", dir.join("day03.txt")).unwrap();
+    let dir = dir.to_str().unwrap();
+    insta::assert_snapshot!(run_with(&["-d", "3", "--input-dir", dir, "--input-template", "day{day:02}.txt"]), @r###"
+    Day 3 - part 1: 8134 ✓
+    "###);
+}
+
+#[test]
+#[serial]
+fn hide_answers() {
+    let expected = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("hidden.txt");
+    std::fs::write(&expected, "Day 1 - part 1: 232\nDay 1 - part 2: 1\n").unwrap();
+    let expected = expected.to_str().unwrap();
+    insta::assert_snapshot!(run_with(&["-d", "1,2", "-m", "--hide-answers", "--expected", expected]), @r###"
+    Day 1 - part 1: ✓
+    Day 1 - part 2: ✗
+    Day 2 - part 1: [hidden]
+    Day 2 - part 2: [hidden]
+    "###);
+    // The default expected results belong to the crate, wherever it is run
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "-p",
+            "dummy-year",
+            "--",
+            "-d",
+            "1",
+            "-m",
+            "--hide-answers",
+        ])
+        .current_dir("..")
+        .output()
+        .unwrap();
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r###"
+    Day 1 - part 1: ✓
+    Day 1 - part 2: ✓
+    "###);
+}
+
+#[test]
+#[serial]
+fn examples() {
+    insta::assert_snapshot!(run_with(&["-a", "--example", "--verify-variants"]), @r###"
+    Input: example 1
+    Day 1 - part 1: 0 ✓
+    Day 1 - part 1 — str_slice: 0 ✓
+    Day 1 - part 2: 5 ✓
+    Day 1 - part 2 — result: 5 ✓
+    Day 1 - part 2 — result_string: 5 ✓
+    "###);
+    assert!(run_with(&["-a", "--example", "2"]).is_empty());
+}
+
+#[test]
+#[serial]
+fn all_inputs() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "-d", "1", "-m", "--all-inputs"])
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r###"
+    Input: default
+    Day 1 - part 1: 232 ✓
+    Day 1 - part 2: 1783 ✓
+
+    Input: alice
+    Day 1 - part 1: 0 ✓
+    Day 1 - part 2: 3 ✓
+    "###);
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("all-inputs");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("expected-alice.txt"), "Day 1 - part 2: 4\n").unwrap();
+    let expected = dir.join("expected.txt");
+    let output = Command::new("cargo")
+        .args(["run", "--", "-d", "1", "-m", "--all-inputs", "--expected"])
+        .arg(&expected)
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r###"
+    Input: default
+    Day 1 - part 1: 232
+    Day 1 - part 2: 1783
+
+    Input: alice
+    Day 1 - part 1: 0
+    Day 1 - part 2: 3 ✗
+
+    Failing inputs: alice
+    "###);
+}
+
+#[test]
+#[serial]
+fn user() {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("users");
+    std::fs::create_dir_all(dir.join("bob")).unwrap();
+    std::fs::write(dir.join("bob/day1.txt"), "(((\n").unwrap();
+    std::fs::write(dir.join("expected-user-bob.txt"), "Day 1 - part 1: 3\n").unwrap();
+    let expected = dir.join("expected.txt");
+    let args = [
+        "-d",
+        "1",
+        "-p",
+        "1",
+        "-m",
+        "--input-dir",
+        dir.to_str().unwrap(),
+        "--expected",
+        expected.to_str().unwrap(),
+        "--hide-answers",
+    ];
+    insta::assert_snapshot!(run_with(&[&args[..], &["--user", "bob"]].concat()), @r###"
+    Day 1 - part 1: ✓
+    "###);
+    let output = Command::new("cargo")
+        .args(["run", "--"])
+        .args(args)
+        .env("AOC_USER", "bob")
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r###"
+    Day 1 - part 1: ✓
+    "###);
+}
+
+#[test]
+#[serial]
+fn user_declared_answers() {
+    let output = Command::new("cargo")
+        .args(["test", "--lib"])
+        .env("AOC_USER", "nobody")
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+#[serial]
+fn harness_report() {
+    let report = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("harness.tap");
+    let _ = std::fs::remove_file(&report);
+    let output = Command::new("cargo")
+        .args(["test", "--test", "check-expected", "--", "day1::part1"])
+        .env("AOC_REPORT", format!("tap:{}", report.display()))
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.starts_with("TAP version 13\n1..2\n"), "{report}");
+    assert!(report.contains("ok 1 - day1::part1::main\n"), "{report}");
+    assert!(
+        report.contains("ok 2 - day1::part1::str_slice\n"),
+        "{report}"
+    );
+}
+
+#[test]
+#[serial]
+fn harness_additional_inputs() {
+    let output = Command::new("cargo")
+        .args([
+            "test",
+            "--test",
+            "check-expected",
+            "--",
+            "--list",
+            "alice::",
+        ])
+        .current_dir("../dummy-year")
+        .output()
+        .unwrap();
+    insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @r###"
+    alice::day1::part1::main: test
+    alice::day1::part1::str_slice: test
+    alice::day1::part2::main: test
+    alice::day1::part2::result: test
+    alice::day1::part2::result_string: test
+    "###);
+}
+
+#[test]
+#[serial]
+fn day1_memory() {
+    let output = run_with_features(&["memory"], &["-d", "1", "-m", "--memory"]);
+    assert_eq!(output.lines().count(), 3, "{output}");
+    assert!(
+        output
+            .lines()
+            .take(2)
+            .all(|l| l.contains(" allocations, ") && l.contains(" allocated, peak heap ")),
+        "{output}"
+    );
+}
+
+#[test]
+#[serial]
+fn all_days_variants() {
+    insta::assert_snapshot!(run_with(&["-a", "--variant", "main", "--variant", "result*", "--exclude-variant", "result"]), @r###"
+    Day 1 - part 1: 232
+    Day 1 - part 2: 1783
+    Day 1 - part 2 — result_string: 1783
+    Day 2 - part 1: 1606483
+    Day 2 - part 2: 20x3x11
+                    15x27x5
+    Day 3 - part 1: 8134 ✓
+
+    "###);
+}
+
+#[test]
+#[serial]
+fn day_ranges() {
+    insta::assert_snapshot!(run_with(&["-d", "1,3-", "-p", "1", "-m"]), @r###"
+    Day 1 - part 1: 232
+    Day 3 - part 1: 8134 ✓
+
+    "###);
+}
+
 #[test]
 #[serial]
 fn day2() {
@@ -45,6 +355,65 @@ fn day2() {
     "###);
 }
 
+#[test]
+#[serial]
+fn day2_markdown() {
+    insta::assert_snapshot!(run_with(&["-d", "2", "--format", "markdown"]), @r###"
+    | Day | Part | Variant | Answer |
+    |----:|-----:|---------|--------|
+    | 2 | 1 | main | `1606483` |
+    | 2 | 2 | main | `20x3x11`<br>`15x27x5` |
+    | 2 | 2 | no_eol | `20x3x11`<br>`15x27x5` |
+    "###);
+}
+
+#[test]
+#[serial]
+fn day1_json() {
+    let output: serde_json::Value =
+        serde_json::from_str(&run_with(&["-d", "1", "-p", "2", "--format", "json"])).unwrap();
+    let solvers = output.as_array().unwrap();
+    assert_eq!(solvers.len(), 3);
+    assert_eq!(solvers[0]["variant"], serde_json::Value::Null);
+    assert_eq!(solvers[1]["variant"], "result");
+    for solver in solvers {
+        assert_eq!(solver["day"], 1);
+        assert_eq!(solver["part"], 2);
+        assert_eq!(solver["answer"], "1783");
+        assert!(solver["time_ns"].is_u64());
+    }
+}
+
+#[test]
+#[serial]
+fn day1_json_errors() {
+    let output: serde_json::Value = serde_json::from_str(&run_with(&[
+        "-d",
+        "1",
+        "-m",
+        "--format",
+        "json",
+        "--input-dir",
+        "nonexistent",
+    ]))
+    .unwrap();
+    let solvers = output.as_array().unwrap();
+    assert_eq!(solvers.len(), 2);
+    for solver in solvers {
+        assert_eq!(solver["answer"], serde_json::Value::Null);
+        assert!(
+            solver["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("error: IO error while loading input")
+        );
+    }
+    insta::assert_snapshot!(run_with(&["-d", "1", "-p", "1", "-m", "--format", "csv", "--input-dir", "nonexistent"]), @r###"
+    day,part,variant,answer,time_ns,peak_rss,allocations,allocated,peak_heap,differs_from_main,error
+    1,1,,,,,,,,false,error: IO error while loading input: No such file or directory (os error 2)
+    "###);
+}
+
 #[test]
 #[serial]
 fn all_days() {
@@ -59,7 +428,7 @@ fn all_days() {
                     15x27x5
     Day 2 - part 2 — no_eol: 20x3x11
                              15x27x5
-    Day 3 - part 1: 8134
+    Day 3 - part 1: 8134 ✓
 
     "###);
 }
@@ -73,7 +442,7 @@ fn all_days_main() {
     Day 2 - part 1: 1606483
     Day 2 - part 2: 20x3x11
                     15x27x5
-    Day 3 - part 1: 8134
+    Day 3 - part 1: 8134 ✓
 
     "###);
 }
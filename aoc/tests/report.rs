@@ -0,0 +1,78 @@
+use aoc::test::{CheckOptions, Report, ReportFormat, check_results_with};
+use aoc_derive::aoc;
+use std::path::PathBuf;
+
+#[aoc(day1, part1)]
+fn day1_part1() -> usize {
+    10
+}
+
+#[aoc(day1, part1, other)]
+fn day1_part1_other() -> usize {
+    20
+}
+
+#[aoc(day2, part1)]
+fn day2_part1() -> String {
+    String::from("a <b>\nc & d\n")
+}
+
+fn register() {
+    aoc::register_runner(1, 1, None, runner_1_1_none);
+    aoc::register_runner(1, 1, Some(String::from("other")), runner_1_1_other);
+    aoc::register_runner(2, 1, None, runner_2_1_none);
+}
+
+fn check(format: ReportFormat, file: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(file);
+    let options = CheckOptions {
+        report: Some(Report {
+            format,
+            path: path.clone(),
+        }),
+        ..Default::default()
+    };
+    assert!(!check_results_with(register, "tests/report.txt", &options).unwrap());
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn hide_times(report: &str) -> String {
+    report
+        .split("time=\"")
+        .enumerate()
+        .map(|(i, s)| match (i, s.split_once('"')) {
+            (0, _) | (_, None) => s.to_owned(),
+            (_, Some((_, rest))) => format!("time=\"_\"{rest}"),
+        })
+        .collect()
+}
+
+#[test]
+fn junit() {
+    insta::assert_snapshot!(hide_times(&check(ReportFormat::JUnit, "report.xml")), @r###"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <testsuites tests="3" failures="1" time="_">
+      <testsuite name="aoc" tests="3" failures="1" time="_">
+        <testcase classname="day1.part1" name="main" time="_"/>
+        <testcase classname="day1.part1" name="other" time="_">
+          <failure message="expected 30, got 20">expected:
+    30
+    actual:
+    20</failure>
+        </testcase>
+        <testcase classname="day2.part1" name="main" time="_"/>
+      </testsuite>
+    </testsuites>
+    "###);
+}
+
+#[test]
+fn tap() {
+    let report = check(ReportFormat::Tap, "report.tap");
+    assert!(report.starts_with("TAP version 13\n1..3\n"));
+    assert!(report.contains("ok 1 - day1::part1::main\n"));
+    assert!(report.contains(
+        "not ok 2 - day1::part1::other\n  ---\n  message: \"expected 30, got 20\"\n  expected: \"30\"\n  actual: \"20\"\n"
+    ));
+    assert!(report.contains("ok 3 - day2::part1::main\n"));
+}
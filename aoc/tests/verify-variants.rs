@@ -0,0 +1,44 @@
+use aoc::test::{CheckOptions, check_results_with};
+use aoc_derive::aoc;
+
+#[aoc(day1, part1)]
+fn day1_part1() -> usize {
+    10
+}
+
+#[aoc(day1, part1, same)]
+fn day1_part1_same() -> usize {
+    10
+}
+
+#[aoc(day1, part1, other)]
+fn day1_part1_other() -> usize {
+    20
+}
+
+#[aoc(day1, part2, alone)]
+fn day1_part2_alone() -> usize {
+    30
+}
+
+fn register() {
+    aoc::register_runner(1, 1, None, runner_1_1_none);
+    aoc::register_runner(1, 1, Some(String::from("same")), runner_1_1_same);
+    aoc::register_runner(1, 1, Some(String::from("other")), runner_1_1_other);
+    aoc::register_runner(1, 2, Some(String::from("alone")), runner_1_2_alone);
+}
+
+#[test]
+fn verify_variants() {
+    assert!(
+        check_results_with(
+            register,
+            "tests/verify-variants.txt",
+            &CheckOptions {
+                verify_variants: true,
+                ..Default::default()
+            }
+        )
+        .unwrap()
+    );
+}
@@ -1,5 +1,9 @@
-#[aoc(day3, part1)]
+#[aoc(day3, part1, answer = 8134)]
 fn part1(mut input: Vec<String>) -> usize {
+    #[cfg(feature = "chatty")]
+    println!("{} lines", input.len());
+    #[cfg(feature = "faulty")]
+    assert!(!input.iter().any(|l| l == "boom"), "boom in solver");
     input.push(String::from("Hello, world"));
     for l in &mut input {
         l.push('x');